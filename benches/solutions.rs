@@ -0,0 +1,30 @@
+extern crate advent_of_code_2023;
+
+use std::env;
+use std::path::Path;
+use std::time::Duration;
+
+use advent_of_code_2023::{get_solver, SOLVERS};
+use advent_of_code_2023::bench::{bench, format_benches};
+use advent_of_code_2023::calendar::{input_path, read_input, PARTS};
+
+// cargo bench [-- <day>...], benchmarks every day when no day is given
+fn main() {
+    let mut solvers = env::args().skip(1)
+        .filter_map(|arg| arg.parse::<u8>().ok())
+        .map(|day| get_solver(day).expect("unknown day"))
+        .collect::<Vec<_>>();
+    if solvers.is_empty() {
+        solvers = SOLVERS.to_vec();
+    }
+
+    let mut benches = vec![];
+    for solver in solvers {
+        let input = read_input(&input_path(Path::new("input"), solver.day())).expect("missing input");
+        let parsed = solver.parse_input(&input).expect("malformed input");
+        for part in PARTS {
+            benches.push(bench(solver.day(), &*parsed, part, 10, Duration::from_secs(10)).expect("solver failed"));
+        }
+    }
+    println!("{}", format_benches(&benches));
+}
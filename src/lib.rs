@@ -0,0 +1,17 @@
+extern crate regex;
+extern crate permute;
+
+mod util;
+
+pub use util::{get_solution, get_solver, Example, Parsed, Part, Result, SolveError, Solver, SOLVERS};
+pub use util::{day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10,
+               day_11, day_12, day_13, day_14, day_15, day_16, day_17, day_18, day_19, day_20,
+               day_21, day_22, day_23, day_24, day_25};
+// Used by the command line and `cargo bench`
+pub use util::{answers, bench, calendar};
+// Helper modules shared by the days, for reuse outside them
+pub use util::{grid, interval, math, parallel, rational, search};
+// Shared types that appear in the public items of the days
+pub use util::grid::{Direction, Grid, Pos};
+pub use util::interval::{Interval, IntervalSet, PiecewiseMap};
+pub use util::rational::Rational;
@@ -1,38 +1,262 @@
-extern crate core;
-extern crate regex;
-extern crate permute;
-extern crate proc_macro;
+extern crate advent_of_code_2023;
 
-use std::io::prelude::*;
 use std::env;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
 
-mod util;
-
-use util::*;
+use advent_of_code_2023::*;
+use advent_of_code_2023::answers::{verify, Answers, Status};
+use advent_of_code_2023::bench;
+use advent_of_code_2023::calendar::{example_input, input_path, load_input, read_input, CalendarRun, PARTS};
 
 fn main() {
 
-    let args: Vec<String> = env::args().collect();
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    if args.len() != 3 {
-        println!("program <day> <part>");
-        std::process::exit(1);
+    match args.first().map(|arg| arg.as_str()) {
+        Some("all") if args.len() == 1 => run_all(),
+        Some("list") if args.len() == 1 => list(),
+        Some("bench") => run_bench(&args[1..]),
+        Some("verify") => run_verify(&args[1..]),
+        Some(day) if args.len() >= 2 && !day.starts_with('-') => run_day(&args[0], &args[1], &args[2..]),
+        _ => {
+            println!("program <day> <part> [--input <file>|<dir>|-] [--example]");
+            println!("program all");
+            println!("program list");
+            println!("program verify [<day>...] [--answers <file>]");
+            println!("program bench [<day>...] [--runs <n>] [--save <file>] [--baseline <file>] [--threshold <percent>]");
+            println!("program 5 trace <seed> [<count>] [--input <file>|<dir>|-] [--example]");
+            println!("program 20 network [--input <file>|<dir>|-] [--presses <n>]");
+            std::process::exit(1);
+        }
     }
+}
+
+// Commands that only make sense for one day, run as `program <day> <command>` instead of a part
+fn day_command(day:u8, command:&str) -> Option<fn(&[String])> {
+    match (day, command) {
+        (5, "trace") => Some(print_trace),
+        (20, "network") => Some(print_network),
+        _ => None,
+    }
+}
+
+fn parse_day(arg:&str) -> u8 {
+    arg.parse::<u8>().unwrap_or_else(|_| exit_with_error(format!("illegal day argument '{}'", arg)))
+}
 
+// Solves one part, by default with the input in input/, or from a file, a directory or stdin with --input
+fn run_day(day:&str, part:&str, args:&[String]) {
     // Read arguments
-    let day = args[1].parse::<u8>().unwrap();
-    let part = match args[2].parse::<u8>() {
-        Ok(1) => Part::Part1,
-        Ok(2) => Part::Part2,
-        _ => panic!("illegal part arguments!")
-    };
+    let solver = get_solver(parse_day(day)).unwrap_or_else(|err| exit_with_error(err.to_string()));
+    if let Some(command) = day_command(solver.day(), part) {
+        return command(args);
+    }
+    let part = part.parse::<u8>()
+        .map_err(|_| format!("illegal part argument '{}'", part))
+        .and_then(|part| Part::new(part).map_err(|err| err.to_string()))
+        .unwrap_or_else(|err| exit_with_error(err));
+
+    let mut path = "input".to_string();
+    let mut example = false;
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--input" => path = it.next().unwrap_or_else(|| exit_with_error("missing value for --input".to_string())).clone(),
+            "--example" => example = true,
+            _ => exit_with_error(format!("unexpected argument '{}'", arg)),
+        }
+    }
 
     // Read input
-    let mut input = String::new();
-    std::io::stdin().read_to_string(&mut input).expect("Failed to fetch input...");
+    let input = if example {
+        example_input(solver, part).map(|input| input.to_string())
+    } else {
+        load_input(Path::new(&path), solver.day())
+    };
+
+    match input.and_then(|input| solver.solve(part, &input)) {
+        Ok(result) => println!("{}",result),
+        Err(err) => exit_with_error(err.to_string()),
+    }
+}
+
+fn list() {
+    for solver in SOLVERS {
+        println!("{:>2}  {}", solver.day(), solver.title());
+    }
+}
+
+// Solves every day with the inputs in input/ and prints a summary table
+fn run_all() {
+    let run = CalendarRun::run(Path::new("input"));
+    println!("{}", run);
+
+    let failures = run.failures();
+    if failures > 0 {
+        exit_with_error(format!("{} parts failed", failures));
+    }
+}
+
+// Benchmarks the given days (all by default) on their inputs in input/
+fn run_bench(args:&[String]) {
+    let mut days = vec![];
+    let mut runs = 10;
+    let mut save = None;
+    let mut baseline = None;
+    let mut threshold = 10.0;
+
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        let mut value = |name:&str| it.next().unwrap_or_else(|| exit_with_error(format!("missing value for {}", name))).clone();
+        match arg.as_str() {
+            "--runs" => runs = value(arg).parse::<usize>().unwrap_or_else(|_| exit_with_error("illegal number of runs".to_string())),
+            "--save" => save = Some(value(arg)),
+            "--baseline" => baseline = Some(value(arg)),
+            "--threshold" => threshold = value(arg).parse::<f64>().unwrap_or_else(|_| exit_with_error("illegal threshold".to_string())),
+            _ => days.push(get_solver(parse_day(arg)).unwrap_or_else(|err| exit_with_error(err.to_string()))),
+        }
+    }
+    if days.is_empty() {
+        days = SOLVERS.to_vec();
+    }
+
+    let mut benches = vec![];
+    let mut parses = vec![];
+    for solver in days {
+        let input = read_input(&input_path(Path::new("input"), solver.day())).unwrap_or_else(|err| exit_with_error(err.to_string()));
+        match bench::bench_parse(solver, &input, runs, Duration::from_secs(10)) {
+            Ok(stats) => parses.push((solver.day(), stats)),
+            Err(err) => eprintln!("day {} parse: {}", solver.day(), err),
+        }
+        let parsed = match solver.parse_input(&input) {
+            Ok(parsed) => parsed,
+            Err(_) => continue,
+        };
+        for part in PARTS {
+            match bench::bench(solver.day(), &*parsed, part, runs, Duration::from_secs(10)) {
+                Ok(result) => benches.push(result),
+                Err(err) => eprintln!("day {} part {}: {}", solver.day(), part.number(), err),
+            }
+        }
+    }
+    println!("{}", bench::format_benches(&benches));
+    println!("{}", bench::format_parses(&parses));
+
+    if let Some(path) = save {
+        fs::write(&path, bench::to_json(&benches)).unwrap_or_else(|err| exit_with_error(format!("cannot write {}: {}", path, err)));
+    }
+
+    if let Some(path) = baseline {
+        let baseline = read_input(Path::new(&path))
+            .and_then(|json| bench::from_json(&json))
+            .unwrap_or_else(|err| exit_with_error(err.to_string()));
+        let comparisons = bench::compare(&baseline, &benches);
+        println!("{}", bench::format_comparisons(&comparisons, threshold / 100.0));
+
+        let regressions = comparisons.iter().filter(|comparison| comparison.is_regression(threshold / 100.0)).count();
+        if regressions > 0 {
+            exit_with_error(format!("{} parts regressed more than {}%", regressions, threshold));
+        }
+    }
+}
+
+// Checks the answers of the given days (all by default) against a file of known answers
+fn run_verify(args:&[String]) {
+    let mut solvers = vec![];
+    let mut path = "answers.toml".to_string();
+
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--answers" => path = it.next().unwrap_or_else(|| exit_with_error("missing value for --answers".to_string())).clone(),
+            _ => solvers.push(get_solver(parse_day(arg)).unwrap_or_else(|err| exit_with_error(err.to_string()))),
+        }
+    }
+    if solvers.is_empty() {
+        solvers = SOLVERS.to_vec();
+    }
+
+    let toml = read_input(Path::new(&path)).unwrap_or_else(|err| exit_with_error(err.to_string()));
+    let answers = Answers::parse(&toml).unwrap_or_else(|err| exit_with_error(format!("{}: {}", path, err)));
+    let verifications = verify(&solvers, Path::new("input"), &answers);
+
+    println!("Day  Part  Status   Answer");
+    for verification in verifications.iter() {
+        println!("{}", verification);
+    }
 
-    let result = get_solution(day, part, input);
+    let count = |status:Status| verifications.iter().filter(|verification| verification.status() == status).count();
+    let failures = count(Status::Fail) + count(Status::Error);
+    println!("\n{} passed, {} failed, {} missing", count(Status::Pass), failures, count(Status::Missing));
+    if failures > 0 {
+        std::process::exit(1);
+    }
+}
+
+// Prints the day 20 modules as a Graphviz graph, with --presses the flip-flop states after that many presses
+fn print_network(args:&[String]) {
+    let mut path = "input".to_string();
+    let mut presses = None;
+
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        let mut value = |name:&str| it.next().unwrap_or_else(|| exit_with_error(format!("missing value for {}", name))).clone();
+        match arg.as_str() {
+            "--input" => path = value(arg),
+            "--presses" => presses = Some(value(arg).parse::<u64>().unwrap_or_else(|_| exit_with_error("illegal number of presses".to_string()))),
+            _ => exit_with_error(format!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    let dot = load_input(Path::new(&path), 20)
+        .and_then(|input| day_20::parse(&input))
+        .and_then(|specs| day_20::Network::new(&specs).map(|mut network| {
+            for _ in 0..presses.unwrap_or(0) {
+                network.press_button();
+            }
+            network.to_dot(presses.is_some())
+        }))
+        .unwrap_or_else(|err| exit_with_error(err.to_string()));
+    print!("{}", dot);
+}
+
+// Prints how day 5 maps a seed, or `count` seeds from it, through every layer of the almanac
+fn print_trace(args:&[String]) {
+    let mut path = "input".to_string();
+    let mut example = false;
+    let mut numbers = vec![];
 
-    println!("{}",result);
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--input" => path = it.next().unwrap_or_else(|| exit_with_error("missing value for --input".to_string())).clone(),
+            "--example" => example = true,
+            _ => numbers.push(arg.parse::<i64>().unwrap_or_else(|_| exit_with_error(format!("unexpected argument '{}'", arg)))),
+        }
+    }
+    let (seed, end) = match numbers[..] {
+        [seed] => (seed, seed.checked_add(1)),
+        [seed, count] if count > 0 => (seed, seed.checked_add(count)),
+        _ => exit_with_error("expected a seed and optionally a positive count".to_string()),
+    };
+    let end = end.unwrap_or_else(|| exit_with_error(format!("the seeds from {} run past the largest i64", seed)));
+    let seeds = Interval::new(seed, end);
+
+    let input = if example {
+        get_solver(5).and_then(|solver| example_input(solver, Part::Part1)).map(|input| input.to_string())
+    } else {
+        load_input(Path::new(&path), 5)
+    };
+    let table = input
+        .and_then(|input| day_05::parse(&input))
+        .map(|almanac| almanac.trace_table(seeds))
+        .unwrap_or_else(|err| exit_with_error(err.to_string()));
+    print!("{}", table);
+}
 
+fn exit_with_error(message: String) -> ! {
+    eprintln!("error: {}", message);
+    std::process::exit(1);
 }
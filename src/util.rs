@@ -1,72 +1,66 @@
-mod day_01;
-mod day_02;
-mod day_03;
-mod day_04;
-mod day_05;
-mod day_06;
-mod day_07;
-mod day_08;
-mod day_09;
-mod day_10;
-mod day_11;
-mod day_12;
-mod day_13;
-mod day_14;
-mod day_15;
-mod day_16;
-mod day_17;
-mod day_18;
-mod day_19;
-mod day_20;
-mod day_21;
-mod day_22;
-mod day_23;
-mod day_24;
-mod day_25;
+// Declares the day modules and registers their solvers, a new day only needs to be added here
+macro_rules! days {
+    ($($day:ident),*) => {
+        $(pub mod $day;)*
+
+        /// The solver of every day, ordered by day
+        pub static SOLVERS:&[&dyn Solver] = &[$(&$day::Solution),*];
+    }
+}
+
+days!(day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10,
+      day_11, day_12, day_13, day_14, day_15, day_16, day_17, day_18, day_19, day_20,
+      day_21, day_22, day_23, day_24, day_25);
+
+pub mod answers;
+pub mod bench;
+pub mod calendar;
+mod error;
+pub mod grid;
+pub mod interval;
+pub mod math;
+pub mod parallel;
+pub mod rational;
+pub mod search;
+mod solver;
+
+pub use self::error::{next_token, parse_token, Result, SolveError};
+pub use self::solver::{Example, Parsed, Puzzle, Solver};
 
 
 
-#[derive(PartialEq,Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum Part {
     Part1,
     Part2,
 }
 
-
-
-pub fn get_solution(day:u8, part:Part, input:String) -> String {
-    match day {
-        1 => day_01::solve(input, part),
-        2 => day_02::solve(input, part),
-        3 => day_03::solve(input, part),
-        4 => day_04::solve(input, part),
-        5 => day_05::solve(input, part),
-        6 => day_06::solve(input, part),
-        7 => day_07::solve(input, part),
-        8 => day_08::solve(input, part),
-        9 => day_09::solve(input, part),
-        10 => day_10::solve(input, part),
-        11 => day_11::solve(input, part),
-        12 => day_12::solve(input, part),
-        13 => day_13::solve(input, part),
-        14 => day_14::solve(input, part),
-        15 => day_15::solve(input, part),
-        16 => day_16::solve(input, part),
-        17 => day_17::solve(input, part),
-        18 => day_18::solve(input, part),
-        19 => day_19::solve(input, part),
-        20 => day_20::solve(input, part),
-        21 => day_21::solve(input, part),
-        22 => day_22::solve(input, part),
-        23 => day_23::solve(input, part),
-        24 => day_24::solve(input, part),
-        25 => day_25::solve(input, part),
-
-        _ => panic!("..."),
+impl Part {
+    pub fn new(part:u8) -> Result<Part> {
+        match part {
+            1 => Ok(Part::Part1),
+            2 => Ok(Part::Part2),
+            _ => Err(SolveError::UnknownPart(part)),
+        }
     }
 
+    pub fn number(&self) -> u8 {
+        match self {
+            Part::Part1 => 1,
+            Part::Part2 => 2,
+        }
+    }
 }
 
 
 
+pub fn get_solver(day:u8) -> Result<&'static dyn Solver> {
+    SOLVERS.iter()
+        .find(|solver| solver.day() == day)
+        .copied()
+        .ok_or(SolveError::UnknownDay(day))
+}
 
+pub fn get_solution(day:u8, part:Part, input:String) -> Result<String> {
+    get_solver(day)?.solve(part, &input)
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+use std::path::Path;
+
+use super::{Part, Result, SolveError, Solver};
+use super::calendar::{input_path, read_input, PARTS};
+
+/// Known answers per day and part, read from a file like `answers.toml`:
+///
+/// ```toml
+/// [day_01]
+/// part1 = "54630"
+/// part2 = "54770"
+/// ```
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Answers<'a> {
+    entries:Vec<(u8, Part, &'a str)>,
+}
+
+impl<'a> Answers<'a> {
+    pub fn parse(toml:&'a str) -> Result<Answers<'a>> {
+        let mut entries = vec![];
+        let mut day = None;
+        for (index, line) in toml.lines().enumerate() {
+            let content = line.split('#').next().unwrap_or("").trim();
+            if content.is_empty() {
+                continue;
+            }
+
+            if let Some(section) = content.strip_prefix('[') {
+                let number = section.strip_suffix(']')
+                    .and_then(|section| section.strip_prefix("day_"))
+                    .ok_or_else(|| SolveError::at_token(index, line, content, "expected section [day_NN]"))?;
+                day = Some(number.parse::<u8>()
+                    .map_err(|_| SolveError::at_token(index, line, number, format!("unexpected '{}'", number)))?);
+                continue;
+            }
+
+            let (key, value) = content.split_once('=')
+                .ok_or_else(|| SolveError::at_token(index, line, content, "expected part1 = \"answer\""))?;
+            let part = match key.trim() {
+                "part1" => Part::Part1,
+                "part2" => Part::Part2,
+                _ => return Err(SolveError::at_token(index, line, key.trim(), "expected part1 or part2")),
+            };
+            let answer = value.trim().strip_prefix('"').and_then(|value| value.strip_suffix('"'))
+                .ok_or_else(|| SolveError::at_token(index, line, value.trim(), "expected a quoted answer"))?;
+            let day = day.ok_or_else(|| SolveError::malformed(index + 1, 1, "answer outside of a [day_NN] section"))?;
+            entries.push((day, part, answer));
+        }
+        Ok(Answers{entries})
+    }
+
+    pub fn get(&self, day:u8, part:Part) -> Option<&'a str> {
+        self.entries.iter()
+            .find(|(d, p, _)| *d == day && *p == part)
+            .map(|(_, _, answer)| *answer)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail,
+    /// No known answer to compare with
+    Missing,
+    Error,
+}
+
+/// Outcome of checking one part against its known answer
+#[derive(Debug)]
+pub struct Verification {
+    pub day:u8,
+    pub part:Part,
+    pub expected:Option<String>,
+    pub answer:Result<String>,
+}
+
+impl Verification {
+    pub fn status(&self) -> Status {
+        match (&self.answer, &self.expected) {
+            (Err(_), _) => Status::Error,
+            (Ok(_), None) => Status::Missing,
+            (Ok(answer), Some(expected)) if answer == expected => Status::Pass,
+            (Ok(_), Some(_)) => Status::Fail,
+        }
+    }
+}
+
+/// Solves both parts of every solver with the inputs in `dir` and checks them against `answers`
+pub fn verify(solvers:&[&dyn Solver], dir:&Path, answers:&Answers) -> Vec<Verification> {
+    let mut verifications = vec![];
+    for solver in solvers {
+        let parsed = read_input(&input_path(dir, solver.day()))
+            .and_then(|input| solver.parse_input(&input));
+        for part in PARTS {
+            let answer = parsed.as_ref()
+                .map_err(|err| err.clone())
+                .and_then(|parsed| parsed.solve(part));
+            let expected = answers.get(solver.day(), part).map(|answer| answer.to_string());
+            verifications.push(Verification{day: solver.day(), part, expected, answer});
+        }
+    }
+    verifications
+}
+
+impl fmt::Display for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:>3}  {:>4}  ", self.day, self.part.number())?;
+        match (self.status(), &self.answer) {
+            (Status::Pass, Ok(answer)) => write!(f, "pass     {}", answer),
+            (Status::Missing, Ok(answer)) => write!(f, "missing  {}", answer),
+            (_, Ok(answer)) => write!(f, "FAIL     {}, expected {}", answer, self.expected.as_deref().unwrap_or("")),
+            (_, Err(err)) => write!(f, "ERROR    {}", err),
+        }
+    }
+}
+
+/// Asserts that `answer` is the known answer in answers.toml, every part must have one recorded
+#[cfg(test)]
+pub fn assert_answer(day:u8, part:Part, answer:Result<String>) {
+    let answers = Answers::parse(include_str!("../../answers.toml")).unwrap();
+    let expected = answers.get(day, part)
+        .unwrap_or_else(|| panic!("no recorded answer for day {} part {}", day, part.number()));
+    assert_eq!(Ok(expected.to_string()), answer);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::{day_01, SOLVERS};
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse("# comment\n[day_01]\npart1 = \"142\" # example\n\n[day_25]\npart2 = \"x\"\n").unwrap();
+        assert_eq!(Some("142"), answers.get(1, Part::Part1));
+        assert_eq!(None, answers.get(1, Part::Part2));
+        assert_eq!(Some("x"), answers.get(25, Part::Part2));
+
+        assert_eq!(Err(SolveError::malformed(2, 1, "expected part1 or part2")), Answers::parse("[day_01]\npart3 = \"1\""));
+        assert_eq!(Err(SolveError::malformed(1, 9, "expected a quoted answer")), Answers::parse("part1 = 1"));
+        assert_eq!(Err(SolveError::malformed(1, 1, "answer outside of a [day_NN] section")), Answers::parse("part1 = \"1\""));
+    }
+
+    #[test]
+    fn test_answers_file() {
+        let answers = Answers::parse(include_str!("../../answers.toml")).unwrap();
+        assert!(answers.entries.iter().all(|(day, _, _)| SOLVERS.iter().any(|solver| solver.day() == *day)));
+        for solver in SOLVERS {
+            for part in PARTS {
+                assert!(answers.get(solver.day(), part).is_some(), "no recorded answer for day {} part {}", solver.day(), part.number());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "no recorded answer for day 26 part 1")]
+    fn test_assert_answer_missing() {
+        assert_answer(26, Part::Part1, Ok("1".to_string()));
+    }
+
+    #[test]
+    fn test_verify() {
+        let answers = Answers::parse("[day_01]\npart1 = \"54630\"\npart2 = \"1\"").unwrap();
+        let verifications = verify(&[&day_01::Solution], Path::new("input"), &answers);
+        let statuses = verifications.iter().map(|verification| verification.status()).collect::<Vec<Status>>();
+        assert_eq!(vec![Status::Pass, Status::Fail], statuses);
+        assert_eq!("  1     2  FAIL     54770, expected 1", verifications[1].to_string());
+
+        let verifications = verify(&[&day_01::Solution], Path::new("no/such/dir"), &Answers::default());
+        assert_eq!(Status::Error, verifications[0].status());
+        assert_eq!(Status::Missing, verify(&[&day_01::Solution], Path::new("input"), &Answers::default())[0].status());
+    }
+}
@@ -0,0 +1,329 @@
+use std::convert::TryFrom;
+use std::fmt::Write;
+use std::iter::Peekable;
+use std::str::Chars;
+use std::time::{Duration, Instant};
+
+use super::{Parsed, Part, Result, SolveError, Solver};
+
+/// Timings of repeated runs of one part
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Stats {
+    pub runs:usize,
+    pub min:Duration,
+    pub median:Duration,
+    pub max:Duration,
+}
+
+impl Stats {
+    pub fn new(mut timings:Vec<Duration>) -> Stats {
+        timings.sort();
+        Stats {
+            runs: timings.len(),
+            min: timings.first().copied().unwrap_or_default(),
+            median: timings.get(timings.len() / 2).copied().unwrap_or_default(),
+            max: timings.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Bench {
+    pub day:u8,
+    pub part:Part,
+    pub stats:Stats,
+}
+
+// Runs `f` up to `runs` times, stopping early once `budget` is spent but always at least once
+fn time<T>(runs:usize, budget:Duration, f:impl Fn() -> Result<T>) -> Result<Stats> {
+    let start = Instant::now();
+    let mut timings = vec![];
+    while timings.is_empty() || timings.len() < runs && start.elapsed() < budget {
+        let run_start = Instant::now();
+        f()?;
+        timings.push(run_start.elapsed());
+    }
+    Ok(Stats::new(timings))
+}
+
+/// Solves `part` of the parsed input of `day` up to `runs` times, stopping early once `budget` is spent
+/// but always at least once. Parsing isn't timed, see `bench_parse`.
+pub fn bench(day:u8, parsed:&dyn Parsed, part:Part, runs:usize, budget:Duration) -> Result<Bench> {
+    let stats = time(runs, budget, || parsed.solve(part))?;
+    Ok(Bench{day, part, stats})
+}
+
+/// Times parsing the input on its own, with the same runs and budget as `bench`
+pub fn bench_parse(solver:&dyn Solver, input:&str, runs:usize, budget:Duration) -> Result<Stats> {
+    time(runs, budget, || solver.parse_input(input).map(|_| ()))
+}
+
+/// Median of a bench compared to the median of the same day and part in a baseline
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Comparison {
+    pub day:u8,
+    pub part:Part,
+    pub baseline:Duration,
+    pub current:Duration,
+}
+
+impl Comparison {
+    /// Relative change, 0.25 is 25% slower than the baseline
+    pub fn change(&self) -> f64 {
+        self.current.as_secs_f64() / self.baseline.as_secs_f64().max(f64::MIN_POSITIVE) - 1.0
+    }
+
+    pub fn is_regression(&self, threshold:f64) -> bool {
+        self.change() > threshold
+    }
+}
+
+/// Compares every bench that has a baseline for the same day and part
+pub fn compare(baseline:&[Bench], current:&[Bench]) -> Vec<Comparison> {
+    current.iter()
+        .filter_map(|bench| baseline.iter()
+            .find(|old| old.day == bench.day && old.part == bench.part)
+            .map(|old| Comparison{day: bench.day, part: bench.part, baseline: old.stats.median, current: bench.stats.median}))
+        .collect()
+}
+
+fn millis(duration:Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+pub fn format_benches(benches:&[Bench]) -> String {
+    let mut table = format!("Day  Part  Runs  {:>12}  {:>12}  {:>12}\n", "Min (ms)", "Median (ms)", "Max (ms)");
+    for bench in benches {
+        let stats = bench.stats;
+        let _ = writeln!(table, "{:>3}  {:>4}  {:>4}  {:>12.3}  {:>12.3}  {:>12.3}",
+                         bench.day, bench.part.number(), stats.runs, millis(stats.min), millis(stats.median), millis(stats.max));
+    }
+    table
+}
+
+pub fn format_parses(parses:&[(u8, Stats)]) -> String {
+    let mut table = format!("Day  Runs  {:>12}  {:>12}  {:>12}\n", "Min (ms)", "Median (ms)", "Max (ms)");
+    for (day, stats) in parses {
+        let _ = writeln!(table, "{:>3}  {:>4}  {:>12.3}  {:>12.3}  {:>12.3}",
+                         day, stats.runs, millis(stats.min), millis(stats.median), millis(stats.max));
+    }
+    table
+}
+
+pub fn format_comparisons(comparisons:&[Comparison], threshold:f64) -> String {
+    let mut table = format!("Day  Part  {:>13}  {:>12}  {:>8}\n", "Baseline (ms)", "Median (ms)", "Change");
+    for comparison in comparisons {
+        let flag = if comparison.is_regression(threshold) { "  regression" } else { "" };
+        let _ = writeln!(table, "{:>3}  {:>4}  {:>13.3}  {:>12.3}  {:>+7.1}%{}",
+                         comparison.day, comparison.part.number(), millis(comparison.baseline), millis(comparison.current),
+                         comparison.change() * 100.0, flag);
+    }
+    table
+}
+
+/// Baseline file with one object per bench, timings in nanoseconds
+pub fn to_json(benches:&[Bench]) -> String {
+    let entries = benches.iter()
+        .map(|bench| format!("  {{\"day\": {}, \"part\": {}, \"runs\": {}, \"min_ns\": {}, \"median_ns\": {}, \"max_ns\": {}}}",
+                             bench.day, bench.part.number(), bench.stats.runs,
+                             bench.stats.min.as_nanos(), bench.stats.median.as_nanos(), bench.stats.max.as_nanos()))
+        .collect::<Vec<String>>();
+    format!("[\n{}\n]\n", entries.join(",\n"))
+}
+
+/// Reads a baseline written by `to_json`, or any JSON array of objects with the same fields
+pub fn from_json(json:&str) -> Result<Vec<Bench>> {
+    let mut reader = JsonReader{chars: json.chars().peekable(), line: 1, column: 1};
+    let mut benches = vec![];
+    reader.expect('[')?;
+    if !reader.next_is(']') {
+        loop {
+            benches.push(reader.bench()?);
+            if reader.next_is(']') {
+                break;
+            }
+            reader.expect(',')?;
+        }
+    }
+    reader.expect(']')?;
+    match reader.peek() {
+        None => Ok(benches),
+        Some(c) => Err(reader.error(format!("unexpected '{}' after the benches", c))),
+    }
+}
+
+// Reads the little JSON a baseline needs, an array of objects with whole number fields, keeping
+// track of where it is for error messages
+struct JsonReader<'a> {
+    chars:Peekable<Chars<'a>>,
+    line:usize,
+    column:usize,
+}
+
+impl<'a> JsonReader<'a> {
+    fn error<S:Into<String>>(&self, message:S) -> SolveError {
+        SolveError::malformed(self.line, self.column, message)
+    }
+
+    // The next character that isn't whitespace, without taking it
+    fn peek(&mut self) -> Option<char> {
+        while let Some(c) = self.chars.peek().copied().filter(|c| c.is_whitespace()) {
+            self.take(c);
+        }
+        self.chars.peek().copied()
+    }
+
+    fn take(&mut self, c:char) {
+        self.chars.next();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+    }
+
+    fn next_is(&mut self, c:char) -> bool {
+        self.peek() == Some(c)
+    }
+
+    fn expect(&mut self, expected:char) -> Result<()> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.take(c);
+                Ok(())
+            },
+            Some(c) => Err(self.error(format!("expected '{}', found '{}'", expected, c))),
+            None => Err(self.error(format!("expected '{}', found the end", expected))),
+        }
+    }
+
+    fn key(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let mut key = String::new();
+        while let Some(c) = self.chars.peek().copied() {
+            self.take(c);
+            match c {
+                '"' => return Ok(key),
+                '\\' | '\n' => return Err(self.error("unexpected character in a field name")),
+                _ => key.push(c),
+            }
+        }
+        Err(self.error("unterminated field name"))
+    }
+
+    fn number(&mut self, key:&str) -> Result<u64> {
+        self.peek();
+        let (line, column) = (self.line, self.column);
+        let mut digits = String::new();
+        while let Some(c) = self.chars.peek().copied().filter(|c| c.is_ascii_alphanumeric() || *c == '.' || *c == '-') {
+            self.take(c);
+            digits.push(c);
+        }
+        digits.parse::<u64>()
+            .map_err(|_| SolveError::malformed(line, column, format!("expected a whole number for \"{}\"", key)))
+    }
+
+    // One object, every field a whole number
+    fn fields(&mut self) -> Result<Vec<(String, u64)>> {
+        let mut fields = vec![];
+        self.expect('{')?;
+        if !self.next_is('}') {
+            loop {
+                let key = self.key()?;
+                self.expect(':')?;
+                let value = self.number(&key)?;
+                fields.push((key, value));
+                if self.next_is('}') {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+        Ok(fields)
+    }
+
+    fn bench(&mut self) -> Result<Bench> {
+        let fields = self.fields()?;
+        let field = |name:&str| fields.iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| *value)
+            .ok_or_else(|| self.error(format!("missing field \"{}\"", name)));
+
+        let (day, part) = (field("day")?, field("part")?);
+        let bench = Bench {
+            day: u8::try_from(day).ok().filter(|day| (1..=25).contains(day))
+                .ok_or_else(|| self.error(format!("day {} is not between 1 and 25", day)))?,
+            part: u8::try_from(part).ok().and_then(|part| Part::new(part).ok())
+                .ok_or_else(|| self.error(format!("unknown part {}", part)))?,
+            stats: Stats {
+                runs: usize::try_from(field("runs")?).map_err(|_| self.error("too many runs"))?,
+                min: Duration::from_nanos(field("min_ns")?),
+                median: Duration::from_nanos(field("median_ns")?),
+                max: Duration::from_nanos(field("max_ns")?),
+            },
+        };
+        self.expect('}')?;
+        Ok(bench)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::day_01;
+
+    fn bench(day:u8, part:Part, millis:u64) -> Bench {
+        let duration = Duration::from_millis(millis);
+        Bench{day, part, stats: Stats{runs: 3, min: duration / 2, median: duration, max: duration * 2}}
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::new(vec![Duration::from_millis(5), Duration::from_millis(1), Duration::from_millis(3)]);
+        assert_eq!(Stats{runs: 3, min: Duration::from_millis(1), median: Duration::from_millis(3), max: Duration::from_millis(5)}, stats);
+    }
+
+    #[test]
+    fn test_bench() {
+        let parsed = day_01::Solution.parse_input("1abc2").unwrap();
+        let result = super::bench(1, &*parsed, Part::Part1, 5, Duration::from_secs(10)).unwrap();
+        assert_eq!((1, Part::Part1, 5), (result.day, result.part, result.stats.runs));
+        let parsed = day_01::Solution.parse_input("abc").unwrap();
+        assert!(super::bench(1, &*parsed, Part::Part1, 5, Duration::from_secs(10)).is_err());
+
+        assert_eq!(3, bench_parse(&day_01::Solution, "1abc2", 3, Duration::from_secs(10)).unwrap().runs);
+        assert!(bench_parse(&day_01::Solution, "1abc2\n\u{e9}", 3, Duration::from_secs(10)).is_err());
+    }
+
+    #[test]
+    fn test_json() {
+        let benches = vec![bench(1, Part::Part1, 10), bench(25, Part::Part2, 7)];
+        assert_eq!(benches, from_json(&to_json(&benches)).unwrap());
+        assert_eq!(Ok(vec![]), from_json("[\n]\n"));
+        // Formatted differently and with the fields in another order
+        let compact = "[{\"part\":2,\"day\":25,\"runs\":3,\"min_ns\":3500000,\"median_ns\":7000000,\"max_ns\":14000000}]";
+        assert_eq!(Ok(vec![bench(25, Part::Part2, 7)]), from_json(compact));
+        let spread = "[\n  {\n    \"day\": 1,\n    \"part\": 1,\n    \"runs\": 3,\n    \"min_ns\": 5000000,\n    \"median_ns\": 10000000,\n    \"max_ns\": 20000000\n  }\n]\n";
+        assert_eq!(Ok(vec![bench(1, Part::Part1, 10)]), from_json(spread));
+
+        assert_eq!(Err(SolveError::malformed(2, 61, "missing field \"max_ns\"")),
+                   from_json("[\n{\"day\": 1, \"part\": 1, \"runs\": 1, \"min_ns\": 1, \"median_ns\": 1}\n]"));
+        assert_eq!(Err(SolveError::malformed(1, 77, "day 281 is not between 1 and 25")),
+                   from_json("[{\"day\": 281, \"part\": 1, \"runs\": 1, \"min_ns\": 1, \"median_ns\": 1, \"max_ns\": 1}]"));
+        assert_eq!(Err(SolveError::malformed(1, 10, "expected a whole number for \"day\"")), from_json("[{\"day\": 1.5}]"));
+        assert_eq!(Err(SolveError::malformed(1, 3, "unexpected ']' after the benches")), from_json("[]]"));
+    }
+
+    #[test]
+    fn test_compare() {
+        let baseline = vec![bench(1, Part::Part1, 10), bench(1, Part::Part2, 10)];
+        let current = vec![bench(1, Part::Part1, 12), bench(1, Part::Part2, 10), bench(2, Part::Part1, 10)];
+        let comparisons = compare(&baseline, &current);
+        assert_eq!(2, comparisons.len());
+        assert!(comparisons[0].is_regression(0.1));
+        assert!(!comparisons[0].is_regression(0.25));
+        assert!(!comparisons[1].is_regression(0.0));
+        assert!(format_comparisons(&comparisons, 0.1).lines().nth(1).unwrap().ends_with("+20.0%  regression"));
+    }
+}
@@ -0,0 +1,188 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::parallel;
+use super::{Part, Result, SolveError, Solver, SOLVERS};
+
+pub const PARTS:[Part;2] = [Part::Part1, Part::Part2];
+
+/// Path of the puzzle input for `day` in `dir`, e.g. `input/input_07.txt`
+pub fn input_path(dir:&Path, day:u8) -> PathBuf {
+    dir.join(format!("input_{:02}.txt", day))
+}
+
+pub fn read_input(path:&Path) -> Result<String> {
+    fs::read_to_string(path)
+        .map_err(|err| SolveError::MissingInput(format!("{}: {}", path.display(), err)))
+}
+
+/// Input of `day` from `path`, which is a file, a directory with inputs named like `input_NN.txt`,
+/// or `-` for stdin
+pub fn load_input(path:&Path, day:u8) -> Result<String> {
+    if path == Path::new("-") {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)
+            .map_err(|err| SolveError::MissingInput(format!("from stdin: {}", err)))?;
+        Ok(input)
+    } else if path.is_dir() {
+        read_input(&input_path(path, day))
+    } else {
+        read_input(path)
+    }
+}
+
+/// The first example from the puzzle text for `part`
+pub fn example_input(solver:&dyn Solver, part:Part) -> Result<&'static str> {
+    solver.examples().iter()
+        .find(|example| example.part == part)
+        .map(|example| example.input)
+        .ok_or_else(|| SolveError::MissingInput(format!("for the example of day {} part {}, there is none", solver.day(), part.number())))
+}
+
+/// Answer of one part of one day together with the time it took to solve it from the parsed input
+#[derive(Debug)]
+pub struct PartRun {
+    pub day:u8,
+    pub part:Part,
+    pub answer:Result<String>,
+    pub elapsed:Duration,
+}
+
+/// Both parts of one day, solved from a single parse of its input
+#[derive(Debug)]
+pub struct DayRun {
+    pub day:u8,
+    pub parse:Duration,
+    pub parts:[PartRun;2],
+}
+
+impl DayRun {
+    pub fn run(solver:&dyn Solver, input:&Result<String>) -> DayRun {
+        let start = Instant::now();
+        let parsed = input.as_ref()
+            .map_err(|err| err.clone())
+            .and_then(|input| solver.parse_input(input));
+        let parse = start.elapsed();
+
+        let parts = PARTS.map(|part| {
+            let start = Instant::now();
+            let answer = parsed.as_ref()
+                .map_err(|err| err.clone())
+                .and_then(|parsed| parsed.solve(part));
+            PartRun{day: solver.day(), part, answer, elapsed: start.elapsed()}
+        });
+        DayRun{day: solver.day(), parse, parts}
+    }
+}
+
+/// Both parts of every day, displayed as a table of answers with parse and solve times
+#[derive(Debug)]
+pub struct CalendarRun {
+    pub days:Vec<DayRun>,
+    pub elapsed:Duration,
+}
+
+impl CalendarRun {
+    /// Solves every day with the inputs found in `dir`, with the `parallel` feature several days at once
+    /// so the time of each part includes competing with the others for the cores
+    pub fn run(dir:&Path) -> CalendarRun {
+        let start = Instant::now();
+        let days = parallel::map(SOLVERS, |solver| DayRun::run(*solver, &read_input(&input_path(dir, solver.day()))));
+        CalendarRun{days, elapsed: start.elapsed()}
+    }
+
+    pub fn parts(&self) -> impl Iterator<Item=&PartRun> {
+        self.days.iter().flat_map(|day| day.parts.iter())
+    }
+
+    pub fn failures(&self) -> usize {
+        self.parts().filter(|run| run.answer.is_err()).count()
+    }
+}
+
+fn millis(duration:Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl fmt::Display for CalendarRun {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let answers = self.parts()
+            .map(|run| match &run.answer {
+                Ok(answer) => answer.clone(),
+                Err(err) => format!("error: {}", err),
+            })
+            .collect::<Vec<String>>();
+        let width = answers.iter().map(|answer| answer.len()).max().unwrap_or(0).max("Answer".len());
+
+        // The parse time of a day is shared by both parts, so it is only on the row of part 1
+        writeln!(f, "Day  Part  {:<width$}  {:>12}  {:>12}", "Answer", "Parse (ms)", "Solve (ms)", width = width)?;
+        let mut answers = answers.iter();
+        for day in self.days.iter() {
+            for (run, answer) in day.parts.iter().zip(answers.by_ref()) {
+                let parse = if run.part == Part::Part1 { format!("{:.3}", millis(day.parse)) } else { String::new() };
+                writeln!(f, "{:>3}  {:>4}  {:<width$}  {:>12}  {:>12.3}", run.day, run.part.number(), answer, parse, millis(run.elapsed), width = width)?;
+            }
+        }
+        write!(f, "{:<width$}  {:>26.3}", "Total", millis(self.elapsed), width = width + 11)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::day_01;
+
+    #[test]
+    fn test_input_path() {
+        assert_eq!(Path::new("input/input_07.txt"), input_path(Path::new("input"), 7));
+        assert_eq!(Path::new("input/input_25.txt"), input_path(Path::new("input"), 25));
+    }
+
+    #[test]
+    fn test_load_input() {
+        let input = load_input(Path::new("input"), 1).unwrap();
+        assert_eq!(input, load_input(Path::new("input/input_01.txt"), 25).unwrap());
+        // The reason after the path is worded by the OS
+        match load_input(Path::new("no/such/input.txt"), 1) {
+            Err(SolveError::MissingInput(message)) => assert!(message.starts_with("no/such/input.txt: ")),
+            other => panic!("expected missing input, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_example_input() {
+        assert_eq!(Ok(day_01::Solution.examples()[0].input), example_input(&day_01::Solution, Part::Part1));
+        assert_eq!(Err(SolveError::MissingInput("for the example of day 25 part 2, there is none".to_string())),
+                   example_input(SOLVERS[24], Part::Part2));
+    }
+
+    #[test]
+    fn test_table() {
+        let run = CalendarRun {
+            days: vec![
+                DayRun::run(&day_01::Solution, &Ok("1abc2".to_string())),
+                DayRun::run(&day_01::Solution, &read_input(Path::new("no/such/input.txt"))),
+            ],
+            elapsed: Duration::from_millis(1500),
+        };
+        assert_eq!(2, run.failures());
+
+        let table = run.to_string();
+        let lines = table.lines().collect::<Vec<&str>>();
+        assert_eq!(6, lines.len());
+        assert!(lines[0].starts_with("Day  Part  Answer"));
+        assert!(lines[0].ends_with("Parse (ms)    Solve (ms)"));
+        assert!(lines[1].starts_with("  1     1  12 "));
+        assert!(lines[2].starts_with("  1     2  12 "));
+        assert!(lines[3].starts_with("  1     1  error: cannot read input no/such/input.txt"));
+        assert!(lines[4].starts_with("  1     2  error: cannot read input no/such/input.txt"));
+        assert_eq!(lines[0].len(), lines[1].len());
+        assert!(lines[5].starts_with("Total"));
+        assert!(lines[5].ends_with("1500.000"));
+        assert_eq!(lines[0].len(), lines[5].len());
+    }
+}
@@ -1,101 +1,152 @@
-use super::Part;
+use super::{Example, Part, Puzzle, Result, SolveError, Solver};
 
 const DIGITS:[&str;10] = ["zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
 
-pub fn solve(input : String, part: Part) -> String {
+pub struct Solution;
 
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+impl Puzzle for Solution {
+    type Input = Vec<String>;
+
+    fn day(&self) -> u8 {
+        1
+    }
+
+    fn title(&self) -> &'static str {
+        "Trebuchet?!"
+    }
+
+    fn parse(&self, input:&str) -> Result<Vec<String>> {
+        parse(input)
+    }
+
+    fn part1(&self, lines:&Vec<String>) -> Result<String> {
+        part1(lines)
+    }
+
+    fn part2(&self, lines:&Vec<String>) -> Result<String> {
+        part2(lines)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
     }
 }
 
-fn part1(lines:Vec<&str>) -> String {
-    lines.iter()
-        .map(|line| 10 * find_digit(line, true, false) + find_digit(line, false, false))
-        .sum::<u32>().to_string()
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
 }
 
-fn part2(lines:Vec<&str>) -> String {
-    lines.iter()
-        .map(|line| 10 * find_digit(line, true, true) + find_digit(line, false, true))
-        .sum::<u32>().to_string()
+// The calibration document, one line per calibration value
+pub fn parse(input:&str) -> Result<Vec<String>> {
+    input.lines()
+        .enumerate()
+        .map(|(index, line)| match line.chars().position(|ch| !ch.is_ascii()) {
+            Some(column) => Err(SolveError::malformed(index + 1, column + 1, "unexpected non-ascii character")),
+            None => Ok(line.to_string()),
+        })
+        .collect()
 }
 
-fn find_digit(line:&str, first:bool, word:bool) -> u32 {
+fn part1(lines:&[String]) -> Result<String> {
+    Ok(lines.iter()
+        .enumerate()
+        .map(|(index, line)| calibration_value(index, line, false))
+        .sum::<Result<u32>>()?
+        .to_string())
+}
 
-    let mut digit_found = 0;
+fn part2(lines:&[String]) -> Result<String> {
+    Ok(lines.iter()
+        .enumerate()
+        .map(|(index, line)| calibration_value(index, line, true))
+        .sum::<Result<u32>>()?
+        .to_string())
+}
+
+fn calibration_value(index:usize, line:&str, word:bool) -> Result<u32> {
+    match (find_digit(line, true, word), find_digit(line, false, word)) {
+        (Some(first), Some(last)) => Ok(10 * first + last),
+        _ => Err(SolveError::malformed(index + 1, 1, format!("no digit in '{}'", line))),
+    }
+}
+
+fn find_digit(line:&str, first:bool, word:bool) -> Option<u32> {
+
+    let mut digit_found = None;
 
     for i in 0..line.len() {
         let sub_string = &line[i..];
 
         let first_char = sub_string.as_bytes()[0] as char;
 
-        if first_char.is_digit(10) {
-            digit_found = first_char as u32 - '0' as u32;
+        if first_char.is_ascii_digit() {
+            digit_found = Some(first_char as u32 - '0' as u32);
         } else if word {
-            for n in 0..DIGITS.len() {
-                if sub_string.starts_with(DIGITS[n]) {
-                    digit_found = n as u32;
+            for (n, digit) in DIGITS.iter().enumerate() {
+                if sub_string.starts_with(digit) {
+                    digit_found = Some(n as u32);
                 }
             }
         }
 
-        if first && digit_found > 0 {
-            return digit_found;
-        } else if i == line.len() - 1 {
+        if first && digit_found.is_some() {
             return digit_found;
         }
 
     }
 
-    panic!("...")
+    digit_found
 }
 
+
+const EXAMPLE:&str = "1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet";
+
+const EXAMPLE_2:&str = "two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen";
+
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "142"},
+    Example{part: Part::Part2, input: EXAMPLE_2, expected: "281"},
+];
+
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
 
 
     #[test]
     fn test1() {
-
-        let input = "1abc2
-pqr3stu8vwx
-a1b2c3d4e5f
-treb7uchet";
-
-        assert_eq!("142", solve(input.to_string(), Part1));
+        assert_eq!("142", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_01.txt");
 
-        assert_eq!("54630", solve(input.to_string(), Part1));
+        assert_answer(1, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-
-        let input = "two1nine
-eightwothree
-abcone2threexyz
-xtwone3four
-4nineeightseven2
-zoneight234
-7pqrstsixteen";
-
-        assert_eq!("281", solve(input.to_string(), Part2));
+        assert_eq!("281", solve(EXAMPLE_2.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_01.txt");
 
-        assert_eq!("54770", solve(input.to_string(), Part2));
+        assert_answer(1, Part2, solve(input.to_string(), Part2));
     }
 }
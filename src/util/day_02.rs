@@ -1,51 +1,81 @@
 use util::day_02::Color::{Blue, Green, Red};
-use super::Part;
+use super::{next_token, parse_token, Example, Part, Puzzle, Result, SolveError, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Vec<Game>;
+
+    fn day(&self) -> u8 {
+        2
+    }
+
+    fn title(&self) -> &'static str {
+        "Cube Conundrum"
+    }
+
+    fn parse(&self, input:&str) -> Result<Vec<Game>> {
+        parse(input)
+    }
+
+    fn part1(&self, games:&Vec<Game>) -> Result<String> {
+        part1(games)
+    }
+
+    fn part2(&self, games:&Vec<Game>) -> Result<String> {
+        part2(games)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
     }
 }
 
-#[derive(PartialEq)]
-enum Color {
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Color {
     Blue,
     Red,
     Green,
 }
-struct Cubes {
-    color:Color,
-    num:usize
+#[derive(Debug)]
+pub struct Cubes {
+    pub color:Color,
+    pub num:usize
 }
 
-fn parse_line(line:&str)  -> Vec<Vec<Cubes>> {
+// The hands of cubes shown in one game
+pub type Game = Vec<Vec<Cubes>>;
+
+fn parse_line(index:usize, line:&str) -> Result<Game> {
     line.
-        split(|c| c == ':' || c == ';')
+        split([':', ';'])
         .map(|s| s.trim())
         .filter(|s| !s.contains("Game"))
         .map(|hand| {
             hand.split(',').map(|h| h.trim())
                 .map(|cubes| {
-                    let items:Vec<&str> = cubes.split(' ').collect();
-                    let num:usize = items[0].parse().unwrap();
-                    let color = match items[1] {
+                    let mut items = cubes.split(' ');
+                    let num:usize = parse_token(index, line, next_token(&mut items, index, line, "number of cubes")?)?;
+                    let color_str = next_token(&mut items, index, line, "cube color")?;
+                    let color = match color_str {
                         "red" => Red,
                         "blue" => Blue,
                         "green" => Green,
-                        _ => panic!("...")
+                        _ => return Err(SolveError::at_token(index, line, color_str, format!("unknown color '{}'", color_str))),
                     };
-                    Cubes{num, color}
+                    Ok(Cubes{num, color})
                 })
                 .collect()
         })
         .collect()
 }
 
-fn game_possible(game:&Vec<Vec<Cubes>>) -> bool {
+fn game_possible(game:&[Vec<Cubes>]) -> bool {
     game.iter().flatten()
-        .into_iter()
         .fold(true, |a , cube| {
             let game_possible = match cube.color {
                 Red => cube.num <= 12,
@@ -57,76 +87,80 @@ fn game_possible(game:&Vec<Vec<Cubes>>) -> bool {
 }
 
 
-fn min_num_cubes(game:&Vec<Vec<Cubes>>) -> usize {
-    let cubes:Vec<&Cubes> = game.into_iter().flatten().collect();
+fn min_num_cubes(game:&[Vec<Cubes>]) -> usize {
+    let cubes:Vec<&Cubes> = game.iter().flatten().collect();
 
-    cubes.iter().filter(|c| c.color == Red).map(|c| c.num).max().unwrap() *
-        cubes.iter().filter(|c| c.color == Blue).map(|c| c.num).max().unwrap() *
-        cubes.iter().filter(|c| c.color == Green).map(|c| c.num).max().unwrap()
+    cubes.iter().filter(|c| c.color == Red).map(|c| c.num).max().unwrap_or(0) *
+        cubes.iter().filter(|c| c.color == Blue).map(|c| c.num).max().unwrap_or(0) *
+        cubes.iter().filter(|c| c.color == Green).map(|c| c.num).max().unwrap_or(0)
+}
+
+pub fn parse(input:&str) -> Result<Vec<Game>> {
+    input.lines()
+        .enumerate()
+        .map(|(index, line)| parse_line(index, line))
+        .collect()
 }
 
-fn part1(lines: Vec<&str>) -> String {
-    lines.iter()
-        .map(|line| parse_line(line))
+fn part1(games:&[Game]) -> Result<String> {
+    Ok(games.iter()
         .enumerate()
-        .filter(|(_, game)| game_possible(&game))
+        .filter(|(_, game)| game_possible(game))
         .map(|(game_no,_)| game_no+1)
         .sum::<usize>()
-        .to_string()
+        .to_string())
 }
 
-fn part2(lines : Vec<&str>) -> String {
-    lines.iter()
-        .map(|line| parse_line(line))
-        .map(|game| min_num_cubes(&game))
+fn part2(games:&[Game]) -> Result<String> {
+    Ok(games.iter()
+        .map(|game| min_num_cubes(game))
         .sum::<usize>()
-        .to_string()
+        .to_string())
 }
 
 
+const EXAMPLE:&str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "8"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "2286"},
+];
+
+
 #[cfg(test)]
 mod tests {
     use std::assert_eq;
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
 
 
     #[test]
     fn test1() {
-
-        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
-
-        assert_eq!("8", solve(input.to_string(), Part1));
+        assert_eq!("8", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_02.txt");
 
-        assert_eq!("2239", solve(input.to_string(), Part1));
+        assert_answer(2, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-
-        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
-Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
-Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
-Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
-Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
-
-        assert_eq!("2286", solve(input.to_string(), Part2));
+        assert_eq!("2286", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_02.txt");
 
-        assert_eq!("83435", solve(input.to_string(), Part2));
+        assert_answer(2, Part2, solve(input.to_string(), Part2));
     }
 }
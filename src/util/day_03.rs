@@ -1,28 +1,61 @@
-use super::Part;
+use super::grid::{Grid, Pos};
+use super::{Example, Part, Puzzle, Result, Solver};
 
 
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Schematic;
+
+    fn day(&self) -> u8 {
+        3
+    }
+
+    fn title(&self) -> &'static str {
+        "Gear Ratios"
+    }
+
+    fn parse(&self, input:&str) -> Result<Schematic> {
+        parse(input)
+    }
+
+    fn part1(&self, schematic:&Schematic) -> Result<String> {
+        part1(schematic)
+    }
+
+    fn part2(&self, schematic:&Schematic) -> Result<String> {
+        part2(schematic)
     }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
+    }
+}
+
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
 }
 
-struct EnginePart {
-    x:usize,
-    y:usize,
-    part_number:u32,
+// A number in the schematic, at the position of its first digit
+#[derive(Debug)]
+pub struct EnginePart {
+    pub x:usize,
+    pub y:usize,
+    pub part_number:u32,
+}
+
+pub struct Schematic {
+    pub map:Grid<char>,
+    pub parts:Vec<EnginePart>,
 }
 
 
 impl EnginePart {
 
-    fn build_parts(lines:&Vec<&str>) -> Vec<EnginePart> {
-       lines.iter()
+    fn build_parts(map:&Grid<char>) -> Vec<EnginePart> {
+       map.rows()
             .enumerate()
-            .map(|(y,line)| parse_from_line(y, line))
-            .flatten()
+            .flat_map(|(y,row)| parse_from_line(y, row))
             .collect()
     }
     fn is_adjacent(&self, x: usize, y: usize) -> bool {
@@ -42,7 +75,7 @@ impl EnginePart {
 }
 
 
-fn parse_from_line(y:usize, line:&str) -> Vec<EnginePart> {
+fn parse_from_line(y:usize, line:&[char]) -> Vec<EnginePart> {
 
     let mut found_number = false;
     let mut part_number = 0;
@@ -50,26 +83,26 @@ fn parse_from_line(y:usize, line:&str) -> Vec<EnginePart> {
     let mut parts = vec![];
     let width = line.len();
 
-    for (xn, ch) in line.bytes().enumerate() {
+    for (xn, &ch) in line.iter().enumerate() {
 
         // Continue on existing number
         if found_number {
-            if (ch as char).is_digit(10) {
+            if let Some(digit) = ch.to_digit(10) {
                 part_number *= 10;
-                part_number += (ch - '0' as u8) as u32;
+                part_number += digit;
             }
 
             // End number
-            if !(ch as char).is_digit(10 ) || xn == width - 1 {
+            if !ch.is_ascii_digit() || xn == width - 1 {
                 // Number ended
                 found_number = false;
                 parts.push(EnginePart{x, y, part_number});
                 part_number = 0;
             }
-        } else if  (ch as char).is_digit(10) {
+        } else if let Some(digit) = ch.to_digit(10) {
             // Found new number
             found_number = true;
-            part_number = (ch - '0' as u8) as u32;
+            part_number = digit;
             x = xn;
         }
     }
@@ -77,93 +110,64 @@ fn parse_from_line(y:usize, line:&str) -> Vec<EnginePart> {
     parts
 }
 
-fn has_neighbor(part:&EnginePart, map:&Vec<Vec<u8>>, is_gear:bool) -> bool {
-
+fn has_neighbor(part:&EnginePart, map:&Grid<char>, is_gear:bool) -> bool {
     let len = part.part_number.to_string().len();
-    let w = map.first().unwrap().len() as i32;
-    let h = map.len() as i32;
-    let y0 = part.y as i32;
-    let x0 = part.x as i32;
-
-    // Left of part and right of part
-    let mut deltas:Vec<(i32,i32)> = vec![(-1,-1),(0,-1),(1,-1), (-1,len as i32),(0,len as i32),(1,len as i32)];
-
-    // Above and below
-    for dx in 0..len {
-        deltas.push((-1,dx as i32));
-        deltas.push((1, dx as i32));
-    }
+    let in_part = |(x, y):Pos| y == part.y && x >= part.x && x < part.x + len;
 
-    deltas.iter()
-        .map(|(yd,xd)| (*yd+y0, *xd+x0))
-        .filter(|(y,x)| *y >=0 && *x >= 0 && *y < h && *x < w)
-        .map(|(y,x)| map.get(y as usize).unwrap().get(x as usize).unwrap())
-        .map(|b| *b as char)
-        .filter(|ch| ch.is_digit(10) || (*ch != '.' && !is_gear) || (*ch == '*' && is_gear))
-        .count() > 0
+    (part.x..part.x + len)
+        .flat_map(|x| map.neighbors8((x, part.y)))
+        .filter(|pos| !in_part(*pos))
+        .map(|pos| map[pos])
+        .any(|ch| ch.is_ascii_digit() || (ch != '.' && !is_gear) || (ch == '*' && is_gear))
 }
 
-fn part1(lines : Vec<&str>) -> String {
+pub fn parse(input:&str) -> Result<Schematic> {
+    let map = Grid::parse(input, |ch| Some(ch).filter(|ch| ch.is_ascii()), "expected an ascii character")?;
+    let parts = EnginePart::build_parts(&map);
+    Ok(Schematic{map, parts})
+}
 
-    let map:Vec<Vec<u8>> = lines.iter()
-        .map(|line| line.bytes().collect())
-        .collect();
+fn part1(schematic:&Schematic) -> Result<String> {
+    let map = &schematic.map;
 
-    EnginePart::build_parts(&lines)
+    Ok(schematic.parts
         .iter()
-        .filter(|part| has_neighbor(&part, &map, false))
+        .filter(|part| has_neighbor(part, map, false))
         .map(|part| part.part_number)
         .sum::<u32>()
-        .to_string()
+        .to_string())
 }
 
-fn part2(lines : Vec<&str>) -> String {
-    let map:Vec<Vec<u8>> = lines.iter()
-        .map(|line| line.bytes().collect())
-        .collect();
+fn part2(schematic:&Schematic) -> Result<String> {
+    let map = &schematic.map;
 
-    let total_parts:Vec<EnginePart> = EnginePart::build_parts(&lines);
-    let parts:Vec<&EnginePart> = total_parts
+    let parts:Vec<&EnginePart> = schematic.parts
         .iter()
-        .filter(|part| has_neighbor(part, &map, true))
+        .filter(|part| has_neighbor(part, map, true))
         .collect();
 
 
     let mut sum = 0;
-    for y in 0..map.len() {
-        for x in 0..map.first().unwrap().len() {
-            let ch = map[y][x] as char;
-            if ch == '*' {
-                // Check for adjacent parts
-                let gears:Vec<&&EnginePart> = parts.iter()
-                    .filter(|part| part.is_adjacent(x,y))
-                    .collect();
-
-                if gears.len() == 2 {
-                    sum += gears[0].part_number * gears[1].part_number;
-                }
+    for ((x, y), ch) in map.iter() {
+        if *ch == '*' {
+            // Check for adjacent parts
+            let gears:Vec<&&EnginePart> = parts.iter()
+                .filter(|part| part.is_adjacent(x,y))
+                .collect();
+
+            if gears.len() == 2 {
+                sum += gears[0].part_number * gears[1].part_number;
             }
         }
     }
 
 
 
-    sum.to_string()
+    Ok(sum.to_string())
 }
 
 
-#[cfg(test)]
-mod tests {
-    use std::assert_eq;
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-    use util::Part::{Part1, Part2};
-
-
-    #[test]
-    fn test1() {
-
-        let input = "467..114..
+const EXAMPLE:&str = "467..114..
 ...*......
 ..35..633.
 ......#...
@@ -174,37 +178,42 @@ mod tests {
 ...$.*....
 .664.598..";
 
-        assert_eq!("4361", solve(input.to_string(), Part1));
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "4361"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "467835"},
+];
+
+
+#[cfg(test)]
+mod tests {
+    use std::assert_eq;
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
+
+
+    #[test]
+    fn test1() {
+        assert_eq!("4361", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_03.txt");
 
-        assert_eq!("519444", solve(input.to_string(), Part1));
+        assert_answer(3, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-
-        let input = "467..114..
-...*......
-..35..633.
-......#...
-617*......
-.....+.58.
-..592.....
-......755.
-...$.*....
-.664.598..";
-
-        assert_eq!("467835", solve(input.to_string(), Part2));
+        assert_eq!("467835", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_03.txt");
 
-        assert_eq!("74528807", solve(input.to_string(), Part2));
+        assert_answer(3, Part2, solve(input.to_string(), Part2));
     }
 }
@@ -1,35 +1,64 @@
-use super::Part;
+use super::{next_token, parse_token, Example, Part, Puzzle, Result, SolveError, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Vec<Card>;
+
+    fn day(&self) -> u8 {
+        4
+    }
+
+    fn title(&self) -> &'static str {
+        "Scratchcards"
+    }
+
+    fn parse(&self, input:&str) -> Result<Vec<Card>> {
+        parse(input)
+    }
+
+    fn part1(&self, cards:&Vec<Card>) -> Result<String> {
+        part1(cards)
+    }
+
+    fn part2(&self, cards:&Vec<Card>) -> Result<String> {
+        part2(cards)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
     }
 }
 
-struct Card{
-    winning:Vec<u32>,
-    numbers:Vec<u32>,
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
+
+#[derive(Debug)]
+pub struct Card{
+    pub winning:Vec<u32>,
+    pub numbers:Vec<u32>,
 }
 
 impl Card {
-    fn new(input:&str) -> Card {
-        let mut it = input.split(|c| c == '|');
-        let mut winning:Vec<u32> = it.next().unwrap().split(|c| c == ' ' || c== ':')
-            .into_iter()
-            .filter(|s| s.starts_with(|c:char| c.is_digit(10)))
-            .map(|s| s.parse::<u32>().unwrap())
-            .collect();
-
-        let numbers = it.next().unwrap().split(|c| c == ' ' || c== ':')
-            .into_iter()
-            .filter(|s| s.starts_with(|c:char| c.is_digit(10)))
-            .map(|s| s.parse::<u32>().unwrap())
-            .collect();
+    fn new(index:usize, input:&str) -> Result<Card> {
+        let mut it = input.split('|');
+        let mut winning:Vec<u32> = Card::parse_numbers(index, input, next_token(&mut it, index, input, "winning numbers")?)?;
+        let numbers = Card::parse_numbers(index, input, next_token(&mut it, index, input, "'|' and numbers")?)?;
+
+        if winning.is_empty() {
+            return Err(SolveError::malformed(index + 1, 1, "missing card number"));
+        }
 
         winning.remove(0);
-        Card{winning, numbers }
+        Ok(Card{winning, numbers })
+    }
+
+    fn parse_numbers(index:usize, line:&str, numbers:&str) -> Result<Vec<u32>> {
+        numbers.split([' ', ':'])
+            .filter(|s| s.starts_with(|c:char| c.is_ascii_digit()))
+            .map(|s| parse_token(index, line, s))
+            .collect()
     }
 
     fn matches(&self) -> usize {
@@ -38,70 +67,84 @@ impl Card {
     fn points(&self) -> usize {
         let matches = self.matches();
         if matches == 0 {
-            return 0;
+            0
         } else {
             let base:usize = 2;
-            return base.pow((matches - 1) as u32);
+            base.pow((matches - 1) as u32)
         }
     }
 }
 
-fn part1(lines : Vec<&str>) -> String {
-    lines.iter()
-        .map(|s| Card::new(s))
-        .map(|card| card.points()).sum::<usize>()
-        .to_string()
+pub fn parse(input:&str) -> Result<Vec<Card>> {
+    input.lines()
+        .enumerate()
+        .map(|(index, s)| Card::new(index, s))
+        .collect()
 }
 
-fn part2(lines : Vec<&str>) -> String {
-    let cards:Vec<Card> =  lines.iter()
-        .map(|s| Card::new(s))
-        .collect();
+fn part1(cards:&[Card]) -> Result<String> {
+    Ok(cards.iter()
+        .map(|card| card.points()).sum::<usize>()
+        .to_string())
+}
 
+fn part2(cards:&[Card]) -> Result<String> {
     let mut number_of_cards = vec![1; cards.len()];
 
-    cards.iter().enumerate().for_each(|(index, card)| {
+    for (index, card) in cards.iter().enumerate() {
+        if index + card.matches() >= cards.len() {
+            return Err(SolveError::malformed(index + 1, 1, "card wins copies past the end of the table"));
+        }
+
         for i in 0..card.matches() {
             number_of_cards[index + i + 1] += number_of_cards[index];
         }
-    });
+    }
 
-    number_of_cards.iter().sum::<u32>().to_string()
+    Ok(number_of_cards.iter().sum::<u32>().to_string())
 }
 
 
+const EXAMPLE:&str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "13"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "30"},
+];
+
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
 
-    const INPUT:&str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
-Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
-Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
-Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
-Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
-Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
 
     #[test]
     fn test1() {
-        assert_eq!("13", solve(INPUT.to_string(), Part1));
+        assert_eq!("13", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_04.txt");
-        assert_eq!("28750", solve(input.to_string(), Part1));
+        assert_answer(4, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("30", solve(INPUT.to_string(), Part2));
+        assert_eq!("30", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_04.txt");
-        assert_eq!("10212704", solve(input.to_string(), Part2));
+        assert_answer(4, Part2, solve(input.to_string(), Part2));
     }
 }
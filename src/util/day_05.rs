@@ -1,209 +1,257 @@
-use std::cmp::{max, min};
 use std::collections::VecDeque;
-use super::Part;
+use std::fmt;
+use std::iter;
+use super::interval::{Interval, IntervalSet, PiecewiseMap};
+use super::{next_token, parse_token, Example, Part, Puzzle, Result, SolveError, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
-    let parts = input.split("\n\n").collect();
-    match part {
-        Part::Part1 => part1(parts),
-        Part::Part2 => part2(parts)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Almanac;
+
+    fn day(&self) -> u8 {
+        5
     }
-}
 
-#[derive(Debug, Eq, PartialEq, PartialOrd, Ord)]
-struct Interval {
-    start:i64,
-    end:i64,
-}
+    fn title(&self) -> &'static str {
+        "If You Give A Seed A Fertilizer"
+    }
 
-impl Interval {
-    fn new(start:i64, end:i64, delta:i64) -> Interval {
-        Interval{start:start + delta, end:end + delta}
+    fn parse(&self, input:&str) -> Result<Almanac> {
+        parse(input)
     }
-}
 
+    fn part1(&self, almanac:&Almanac) -> Result<String> {
+        part1(almanac)
+    }
 
-#[derive(Debug)]
-struct Mapper {
-    destination:i64,
-    source:i64,
-    size:i64,
+    fn part2(&self, almanac:&Almanac) -> Result<String> {
+        part2(almanac)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
+    }
 }
 
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
 
+#[derive(Debug)]
+pub struct Mapper {
+    pub destination:i64,
+    pub source:i64,
+    pub size:i64,
+}
 
 impl Mapper {
-    fn new(line:&str) -> Mapper {
-        let mut it = line.split(' ').into_iter().map(|s| s.parse::<i64>().unwrap());
-        let destination = it.next().unwrap();
-        let source = it.next().unwrap();
-        let size = it.next().unwrap();
-        Mapper{destination, source, size}
+    fn new(index:usize, line:&str) -> Result<Mapper> {
+        let mut it = line.split(' ');
+        let destination = parse_token(index, line, next_token(&mut it, index, line, "destination")?)?;
+        let source = parse_token(index, line, next_token(&mut it, index, line, "source")?)?;
+        let size = parse_token(index, line, next_token(&mut it, index, line, "size")?)?;
+        Ok(Mapper{destination, source, size})
     }
 
-    fn intersects_single(&self, source:i64) -> bool {
-        source >= self.source && source < self.source + self.size
+    // The source range and how far it moves
+    fn piece(&self) -> (Interval, i64) {
+        (Interval::new(self.source, self.source + self.size), self.destination - self.source)
     }
+}
 
-    fn transform_single(&self, source:i64) -> i64 {
-        self.destination - self.source + source
+/// Written as in the almanac, destination, source and size
+impl fmt::Display for Mapper {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.destination, self.source, self.size)
     }
+}
 
-    fn intersects_interval(&self, interval:&Interval) -> bool {
-        let source_start = self.source;
-        let source_end = self.source + self.size - 1;
-        return  !(interval.end < source_start || interval.start > source_end)
-    }
 
-    fn transform_interval(&self, interval:&Interval) -> (Vec<Interval>, Vec<Interval>) {
-        let source_start = self.source;
-        let source_end = self.source + self.size - 1;
-        let delta = self.destination - self.source;
+// One map of the almanac, e.g. seed-to-soil
+#[derive(Debug)]
+pub struct Layer {
+    pub name: String,
+    pub mappers: Vec<Mapper>
+}
+
+impl Layer {
+    fn new(first_index:usize, mut lines:VecDeque<&str>) -> Result<Layer> {
+        let header = lines.pop_front().unwrap_or("");
+        let name = header.strip_suffix(" map:")
+            .ok_or_else(|| SolveError::missing(first_index, header, "' map:'"))?
+            .to_string();
+        let mappers = lines.into_iter()
+            .enumerate()
+            .map(|(i, line)| Mapper::new(first_index + i + 1, line))
+            .collect::<Result<Vec<Mapper>>>()?;
+        Ok(Layer { name, mappers })
+    }
 
-        let mut mapped_interval = vec![];
-        let mut unmapped_interval = vec![];
+    /// Category the layer maps from, e.g. `seed`
+    pub fn source(&self) -> &str {
+        self.name.split("-to-").next().unwrap_or(&self.name)
+    }
 
-        // is interval before or after mapper?
-        if interval.start < source_start {
-            // Before part
-            unmapped_interval.push(Interval::new(interval.start, source_start-1, 0 ));
-        }
+    /// Category the layer maps to, e.g. `soil`
+    pub fn destination(&self) -> &str {
+        self.name.rsplit("-to-").next().unwrap_or(&self.name)
+    }
 
-        // Overlapping part
-        mapped_interval.push(Interval::new(max(source_start, interval.start), min(source_end, interval.end), delta));
+    /// The layer as a map, where ranges overlap the first listed applies
+    pub fn map(&self) -> PiecewiseMap {
+        PiecewiseMap::new(self.mappers.iter().map(|mapper| mapper.piece()))
+    }
 
-        // does interval go beyond mapper?
-        if interval.end > source_end {
-            // After part
-            unmapped_interval.push(Interval::new(source_end+1, interval.end, 0));
+    // The values split into the parts each mapper applies to, with the index of that mapper, and
+    // the parts no mapper applies to
+    fn split(&self, values:Interval) -> Vec<(Interval, Option<usize>)> {
+        let mut rest = IntervalSet::from(values);
+        let mut parts = vec![];
+        for (index, mapper) in self.mappers.iter().enumerate() {
+            let mapped = rest.intersection(&IntervalSet::from(mapper.piece().0));
+            parts.extend(mapped.intervals().iter().map(|part| (*part, Some(index))));
+            rest = rest.difference(&mapped);
         }
-
-        (unmapped_interval, mapped_interval)
+        parts.extend(rest.intervals().iter().map(|part| (*part, None)));
+        parts.sort();
+        parts
     }
 }
 
+/// Where a trace is after one layer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    /// Category the values are in, e.g. `soil`
+    pub category:String,
+    pub values:Interval,
+    /// Index of the mapper of the layer that moved the values here, `None` where they kept their
+    /// number or for the seeds themselves
+    pub mapper:Option<usize>,
+}
 
 #[derive(Debug)]
-struct Layer {
-    mappers: Vec<Mapper>
+pub struct Almanac {
+    pub seeds:Vec<i64>,
+    pub layers:Vec<Layer>,
 }
 
-impl Layer {
-    fn new(mut lines:VecDeque<&str>) -> Layer {
-        // pop first
-        lines.pop_front();
-        Layer { mappers:lines.into_iter().map(|line| Mapper::new(line)).collect()}
+impl Almanac {
+    /// Every layer composed into a single map from seed straight to location
+    pub fn seed_to_location(&self) -> PiecewiseMap {
+        self.layers.iter().fold(PiecewiseMap::identity(), |map, layer| map.compose(&layer.map()))
     }
 
-    fn convert(&self, source:i64) -> i64 {
-        for interval in self.mappers.iter() {
-            if interval.intersects_single(source) {
-                return interval.transform_single(source);
-            }
-        }
-        source
+    /// The map from location back to the seed leading there, if no two seeds lead to the same
+    /// location
+    pub fn location_to_seed(&self) -> Option<PiecewiseMap> {
+        self.seed_to_location().invert()
     }
 
-    fn intersects(&self, interval:&Interval) -> bool {
-        self.mappers.iter().filter(|mapper| mapper.intersects_interval(interval)).count() > 0
+    /// How the seeds travel through every layer, one trace for each part of the seeds that the same
+    /// mappers apply to all the way, in order of seed
+    pub fn trace(&self, seeds:Interval) -> Vec<Vec<TraceStep>> {
+        let first = self.layers.first().map_or("seed", |layer| layer.source());
+        let mut traces = vec![vec![TraceStep{category: first.to_string(), values: seeds, mapper: None}]];
+        for layer in self.layers.iter() {
+            traces = traces.into_iter().flat_map(|trace| {
+                let values = trace.last().unwrap().values;
+                layer.split(values).into_iter().map(move |(part, mapper)| {
+                    // Earlier steps only keep the values that end up in this part
+                    let kept = Interval::new(0, part.len() as i64).shift(part.start - values.start);
+                    let mut trace = trace.iter()
+                        .map(|step| TraceStep{values: kept.shift(step.values.start), ..step.clone()})
+                        .collect::<Vec<TraceStep>>();
+                    let moved = mapper.map_or(0, |index| layer.mappers[index].piece().1);
+                    trace.push(TraceStep{category: layer.destination().to_string(), values: part.shift(moved), mapper});
+                    trace
+                }).collect::<Vec<Vec<TraceStep>>>()
+            }).collect();
+        }
+        traces
     }
-    fn transform(&self, interval:Interval) -> Vec<Interval> {
-        let mut transformed_intervals = vec![];
-        let mut unmapped_intervals = VecDeque::new();
-
-        // Add original interval to be evaluated
-        unmapped_intervals.push_back(interval);
-
-        while !unmapped_intervals.is_empty() {
-            let unmapped_interval =  unmapped_intervals.pop_front().unwrap();
-
-            if self.intersects(&unmapped_interval) {
-                // Transform it
-                for mapper in self.mappers.iter() {
-                    if mapper.intersects_interval(&unmapped_interval) {
-                        let (unmapped, mut mapped) = mapper.transform_interval(&unmapped_interval);
-
-                        // Add transformed ones
-                        transformed_intervals.append(&mut mapped);
-
-                        // Add unmapped
-                        unmapped_intervals.append(&mut unmapped.into_iter().collect::<VecDeque<Interval>>());
-                    }
-                }
-            } else {
-                // Add as is to transformed
-                transformed_intervals.push(unmapped_interval);
+
+    /// The traces of `seeds` as a table, one row per layer with the mapper that applied, separated
+    /// by a blank line
+    pub fn trace_table(&self, seeds:Interval) -> String {
+        let traces = self.trace(seeds);
+        let mut rows = vec![];
+        for (index, trace) in traces.iter().enumerate() {
+            if index > 0 {
+                rows.push(("".to_string(), "".to_string(), "".to_string()));
+            }
+            rows.push(("Category".to_string(), "Values".to_string(), "Mapper".to_string()));
+            for (step, layer) in trace.iter().zip(iter::once(None).chain(self.layers.iter().map(Some))) {
+                let values = match step.values.len() {
+                    1 => step.values.start.to_string(),
+                    _ => format!("{}..={}", step.values.start, step.values.end - 1),
+                };
+                let mapper = match (layer, step.mapper) {
+                    (None, _) => "".to_string(),
+                    (Some(layer), Some(index)) => layer.mappers[index].to_string(),
+                    (Some(_), None) => "-".to_string(),
+                };
+                rows.push((step.category.clone(), values, mapper));
             }
         }
 
-        transformed_intervals
+        let category_width = rows.iter().map(|(category, _, _)| category.len()).max().unwrap_or(0);
+        let values_width = rows.iter().map(|(_, values, _)| values.len()).max().unwrap_or(0);
+        rows.iter()
+            .map(|(category, values, mapper)| format!("{:category_width$}  {:values_width$}  {}", category, values, mapper).trim_end().to_string() + "\n")
+            .collect()
     }
 }
 
-fn parse(parts : Vec<&str>) -> (Vec<i64>,Vec<Layer>) {
+pub fn parse(input:&str) -> Result<Almanac> {
+    let parts:Vec<&str> = input.split("\n\n").collect();
     let seeds:Vec<i64> = parts[0].split(' ')
-        .into_iter()
-        .filter(|s| (s.as_bytes()[0] as char).is_digit(10) )
-        .map(|s| s.parse().unwrap())
-        .collect();
+        .filter(|s| s.starts_with(|c:char| c.is_ascii_digit()))
+        .map(|s| parse_token(0, parts[0], s))
+        .collect::<Result<Vec<i64>>>()?;
 
-    let mut mappers: Vec<Layer> = vec![];
-    for part in parts[1..].into_iter() {
+    if seeds.is_empty() {
+        return Err(SolveError::malformed(1, 1, "missing seeds"));
+    }
+
+    let mut layers: Vec<Layer> = vec![];
+    let mut index = parts[0].lines().count() + 1;
+    for part in parts[1..].iter() {
         let lines = part.lines().collect::<VecDeque<&str>>();
-        mappers.push(Layer::new(lines));
+        layers.push(Layer::new(index, lines)?);
+        index += part.lines().count() + 1;
     }
 
-    (seeds, mappers)
+    Ok(Almanac{seeds, layers})
 }
 
-fn part1(parts : Vec<&str>) -> String {
-    let (seeds, mappers) = parse(parts);
-    seeds.iter()
-        .map(|seed| {
-            let mut next = *seed;
-            for mapper in mappers.iter() {
-                next = mapper.convert(next);
-            }
-            next
-        })
+fn part1(almanac:&Almanac) -> Result<String> {
+    let map = almanac.seed_to_location();
+    Ok(almanac.seeds.iter()
+        .map(|seed| map.apply(*seed))
         .min()
         .unwrap()
-        .to_string()
-
+        .to_string())
 }
 
-fn part2(parts : Vec<&str>) -> String {
-    let (seeds, layers) = parse(parts);
+fn part2(almanac:&Almanac) -> Result<String> {
+    let seeds = &almanac.seeds;
 
-    let mut intervals = seeds
-        .chunks(2)
-        .map(|chunk| Interval{start:chunk[0], end:chunk[0] + chunk[1] - 1})
-        .collect::<VecDeque<Interval>>();
-
-    for layer in layers.iter() {
-        let mut next_intervals = VecDeque::new();
-        while !intervals.is_empty() {
-            let interval = intervals.pop_front().unwrap();
-            let next = layer.transform(interval);
-            next_intervals.append(&mut next.into_iter().collect());
-        }
-
-        intervals.append(&mut next_intervals);
+    if !seeds.len().is_multiple_of(2) {
+        return Err(SolveError::malformed(1, 1, "seed ranges must come in pairs"));
     }
 
-    intervals.iter().map(|interval| interval.start).min().unwrap().to_string()
+    let seed_set = seeds
+        .chunks(2)
+        .map(|chunk| Interval::new(chunk[0], chunk[0] + chunk[1]))
+        .collect::<IntervalSet>();
+    almanac.seed_to_location().apply_set(&seed_set).min()
+        .map(|location| location.to_string())
+        .ok_or_else(|| SolveError::unsolvable("no seed ranges to map"))
 }
 
 
-#[cfg(test)]
-mod tests {
-    use std::assert_eq;
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-    use util::Part::{Part1, Part2};
-
-
-    const INPUT:&str = "seeds: 79 14 55 13
+const EXAMPLE:&str = "seeds: 79 14 55 13
 
 seed-to-soil map:
 50 98 2
@@ -237,25 +285,104 @@ humidity-to-location map:
 60 56 37
 56 93 4";
 
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "35"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "46"},
+];
+
+
+#[cfg(test)]
+mod tests {
+    use std::assert_eq;
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
+
+
     #[test]
     fn test1() {
-        assert_eq!("35", solve(INPUT.to_string(), Part1));
+        assert_eq!("35", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_05.txt");
-        assert_eq!("331445006", solve(input.to_string(), Part1));
+        assert_answer(5, Part1, solve(input.to_string(), Part1));
+    }
+
+    #[test]
+    fn test_layer_map() {
+        let almanac = parse(EXAMPLE).unwrap();
+        let map = almanac.layers[0].map();
+        assert_eq!("seed-to-soil", almanac.layers[0].name);
+        assert_eq!(&[(Interval::new(50, 98), 2), (Interval::new(98, 100), -48)], map.pieces());
+        assert_eq!(vec![81, 14, 57, 13], almanac.seeds.iter().map(|seed| map.apply(*seed)).collect::<Vec<i64>>());
+        assert_eq!(Some(map.clone()), map.invert().and_then(|inverse| inverse.invert()));
+    }
+
+    #[test]
+    fn test_seed_to_location() {
+        let almanac = parse(EXAMPLE).unwrap();
+        let map = almanac.seed_to_location();
+        for seed in 0..120 {
+            let location = almanac.layers.iter().fold(seed, |value, layer| layer.map().apply(value));
+            assert_eq!(location, map.apply(seed));
+        }
+        assert_eq!(vec![82, 43, 86, 35], almanac.seeds.iter().map(|seed| map.apply(*seed)).collect::<Vec<i64>>());
+
+        let inverse = almanac.location_to_seed().unwrap();
+        assert_eq!((13, 82), (inverse.apply(35), inverse.apply(46)));
+        assert_eq!(PiecewiseMap::identity(), map.compose(&inverse));
+    }
+
+    #[test]
+    fn test_trace() {
+        let almanac = parse(EXAMPLE).unwrap();
+        let trace = almanac.trace(Interval::inclusive(79, 79));
+        assert_eq!(1, trace.len());
+        let steps = trace[0].iter().map(|step| (step.category.as_str(), step.values.start, step.mapper)).collect::<Vec<(&str, i64, Option<usize>)>>();
+        assert_eq!(vec![("seed", 79, None), ("soil", 81, Some(1)), ("fertilizer", 81, None), ("water", 81, None),
+                        ("light", 74, Some(1)), ("temperature", 78, Some(2)), ("humidity", 78, None), ("location", 82, Some(0))], steps);
+
+        // Every part of the range is traced once, each ending where the composed map takes it
+        let seeds = Interval::inclusive(79, 92);
+        let traces = almanac.trace(seeds);
+        assert!(traces.len() > 1);
+        assert_eq!(IntervalSet::from(seeds), traces.iter().map(|trace| trace[0].values).collect::<IntervalSet>());
+        assert_eq!(seeds.len(), traces.iter().map(|trace| trace[0].values.len()).sum::<u64>());
+        let map = almanac.seed_to_location();
+        for trace in traces.iter() {
+            assert_eq!(map.apply(trace[0].values.start), trace[7].values.start);
+            assert!(trace.iter().all(|step| step.values.len() == trace[0].values.len()));
+        }
+    }
+
+    #[test]
+    fn test_trace_table() {
+        let almanac = parse(EXAMPLE).unwrap();
+        let table = "Category     Values  Mapper
+seed         14
+soil         14      -
+fertilizer   53      39 0 15
+water        49      49 53 8
+light        42      18 25 70
+temperature  42      -
+humidity     43      1 0 69
+location     43      -
+";
+        assert_eq!(table, almanac.trace_table(Interval::inclusive(14, 14)));
+        assert!(almanac.trace_table(Interval::inclusive(79, 92)).contains("\n\nCategory"));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("46", solve(INPUT.to_string(), Part2));
+        assert_eq!("46", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_05.txt");
-        assert_eq!("6472060", solve(input.to_string(), Part2));
+        assert_answer(5, Part2, solve(input.to_string(), Part2));
     }
 }
@@ -1,86 +1,136 @@
-use super::Part;
+use super::{parse_token, Example, Part, Puzzle, Result, SolveError, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Vec<Race>;
+
+    fn day(&self) -> u8 {
+        6
+    }
+
+    fn title(&self) -> &'static str {
+        "Wait For It"
+    }
+
+    fn parse(&self, input:&str) -> Result<Vec<Race>> {
+        parse(input)
+    }
+
+    fn part1(&self, races:&Vec<Race>) -> Result<String> {
+        part1(races)
+    }
+
+    fn part2(&self, races:&Vec<Race>) -> Result<String> {
+        part2(races)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
     }
 }
 
-struct Race {
-    time:u64,
-    distance:u64,
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
+
+#[derive(Debug)]
+pub struct Race {
+    pub time:u64,
+    pub distance:u64,
 }
 
 impl Race {
     fn race(&self) -> usize {
-        (1..self.time).into_iter()
+        (1..self.time)
             .map(|hold| (self.time - hold) * hold)
             .filter(|dist| *dist > self.distance)
             .count()
     }
 }
 
-fn parse(lines: Vec<&str>) -> Vec<Race> {
+pub fn parse(input:&str) -> Result<Vec<Race>> {
     let mut races = vec![];
-    let numbers:Vec<Vec<u64>> = lines.iter()
-        .map(|s| s.split(':').last().unwrap())
-        .map(|s| s.split(' ')
-            .filter(|s| s.starts_with(|c:char| c.is_digit(10)))
-            .map(|s| s.parse::<u64>().unwrap())
-            .collect::<Vec<u64>>())
-        .collect();
-
-    for i in 0..numbers.first().unwrap().len() {
-        races.push(Race{time:numbers[0][i], distance:numbers[1][i]});
+    let numbers:Vec<Vec<u64>> = input.lines()
+        .enumerate()
+        .map(|(index, line)| line.split(':').next_back().unwrap()
+            .split(' ')
+            .filter(|s| s.starts_with(|c:char| c.is_ascii_digit()))
+            .map(|s| parse_token(index, line, s))
+            .collect::<Result<Vec<u64>>>())
+        .collect::<Result<Vec<Vec<u64>>>>()?;
+
+    if numbers.len() != 2 || numbers[0].len() != numbers[1].len() {
+        return Err(SolveError::malformed(1, 1, "expected one line of times and one line of distances"));
+    }
+
+    for (time, distance) in numbers[0].iter().zip(numbers[1].iter()) {
+        races.push(Race{time:*time, distance:*distance});
     }
-    races
+    Ok(races)
 }
 
 
-fn part1(lines : Vec<&str>) -> String {
-    let races = parse(lines);
-    races.iter().map(|race| race.race()).product::<usize>().to_string()
+// Appends the digits of `b` to `a`
+fn concat(a:u64, b:u64) -> Result<u64> {
+    let digits = b.to_string().len() as u32;
+    10u64.checked_pow(digits)
+        .and_then(|shift| a.checked_mul(shift))
+        .and_then(|a| a.checked_add(b))
+        .ok_or_else(|| SolveError::unsolvable("race does not fit in 64 bits"))
 }
 
-fn part2(lines : Vec<&str>) -> String {
-    let next:Vec<String> = lines.iter().map(|line| line.replace(" ", "")).collect();
-    let races = parse(next.iter().map(|s|s.as_str()).collect());
-    races.iter().map(|race| race.race()).product::<usize>().to_string()
+fn part1(races:&[Race]) -> Result<String> {
+    Ok(races.iter().map(|race| race.race()).product::<usize>().to_string())
+}
+
+fn part2(races:&[Race]) -> Result<String> {
+    // There is only one race, the spaces between the numbers are bad kerning
+    let mut race = Race{time:0, distance:0};
+    for next in races {
+        race = Race{time:concat(race.time, next.time)?, distance:concat(race.distance, next.distance)?};
+    }
+    Ok(race.race().to_string())
 }
 
 
+const EXAMPLE:&str = "Time:      7  15   30
+Distance:  9  40  200";
+
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "288"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "71503"},
+];
+
+
 #[cfg(test)]
 mod tests {
     use std::assert_eq;
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
 
 
-    const INPUT:&str = "Time:      7  15   30
-Distance:  9  40  200";
-
     #[test]
     fn test1() {
-        assert_eq!("288", solve(INPUT.to_string(), Part1));
+        assert_eq!("288", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_06.txt");
-        assert_eq!("781200", solve(input.to_string(), Part1));
+        assert_answer(6, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("71503", solve(INPUT.to_string(), Part2));
+        assert_eq!("71503", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_06.txt");
-        assert_eq!("49240091", solve(input.to_string(), Part2));
+        assert_answer(6, Part2, solve(input.to_string(), Part2));
     }
 }
@@ -1,49 +1,56 @@
 use std::char;
-use std::cmp::Ordering;
-use util::day_07::HandType::{FIVE, FOUR, FullHouse, PAIR, THREE, HighCard, TwoPairs};
-use super::Part;
+use util::day_07::HandType::{Five, Four, FullHouse, Pair, Three, HighCard, TwoPairs};
+use super::{next_token, parse_token, Example, Part, Puzzle, Result, SolveError, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Vec<Hand>;
+
+    fn day(&self) -> u8 {
+        7
+    }
+
+    fn title(&self) -> &'static str {
+        "Camel Cards"
+    }
+
+    fn parse(&self, input:&str) -> Result<Vec<Hand>> {
+        parse(input)
+    }
+
+    fn part1(&self, hands:&Vec<Hand>) -> Result<String> {
+        part1(hands)
+    }
+
+    fn part2(&self, hands:&Vec<Hand>) -> Result<String> {
+        part2(hands)
     }
-}
 
-#[derive(PartialEq, Eq)]
-struct Hand {
-    hand_type:HandType,
-    hand_value:usize,
-    cards:Vec<char>,
-    bid:u32,
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
+    }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
-enum HandType {
-    FIVE = 7,
-    FOUR = 6,
-    FullHouse = 5,
-    THREE = 4,
-    TwoPairs = 3,
-    PAIR = 2,
-    HighCard = 1,
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
 }
 
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        if self.hand_type != other.hand_type {
-            self.hand_type.cmp(&other.hand_type)
-        } else {
-            self.hand_value.cmp(&other.hand_value)
-        }
-    }
+#[derive(Debug, PartialEq, Eq)]
+pub struct Hand {
+    pub cards:Vec<char>,
+    pub bid:u32,
 }
 
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HandType {
+    Five = 7,
+    Four = 6,
+    FullHouse = 5,
+    Three = 4,
+    TwoPairs = 3,
+    Pair = 2,
+    HighCard = 1,
 }
 
 impl Hand {
@@ -52,16 +59,24 @@ impl Hand {
     const CARDS_VALUES_PART2:[char;13] = ['A', 'K', 'Q', 'T', '9', '8', '7', '6', '5', '4', '3', '2', 'J'];
     const NUMBER_OF_CARDS:usize = Hand::CARDS_VALUES.len();
 
-    fn new(line:&str, part2:bool) -> Hand {
+    fn new(index:usize, line:&str) -> Result<Hand> {
         let mut it = line.split(' ');
-        let cards = it.next().unwrap().chars().collect();
-        let bid = it.next().unwrap().parse().unwrap();
-        let hand_type = Hand::get_hand_type(&cards, part2);
-        let hand_value = Hand::get_hand_value(&cards, part2);
-        Hand{hand_type, hand_value, cards, bid}
+        let cards_str = next_token(&mut it, index, line, "cards")?;
+        let cards:Vec<char> = cards_str.chars().collect();
+        if cards.len() != 5 || !cards.iter().all(|card| Hand::CARDS_VALUES.contains(card)) {
+            return Err(SolveError::at_token(index, line, cards_str, format!("expected five cards, found '{}'", cards_str)));
+        }
+
+        let bid = parse_token(index, line, next_token(&mut it, index, line, "bid")?)?;
+        Ok(Hand{cards, bid})
+    }
+
+    // Hands are ordered by type first and then by the value of the cards in order
+    fn strength(&self, part2:bool) -> (HandType, usize) {
+        (Hand::get_hand_type(&self.cards, part2), Hand::get_hand_value(&self.cards, part2))
     }
 
-    fn get_hand_value(cards:&Vec<char>, part2:bool) -> usize {
+    fn get_hand_value(cards:&[char], part2:bool) -> usize {
         let mut hand_value = 0;
         for i in 0..cards.len() {
             hand_value += Hand::get_card_value(cards[i], part2);
@@ -86,7 +101,7 @@ impl Hand {
             .unwrap()
     }
 
-    fn get_hand_type_no_jokers(cards:&Vec<char>) -> HandType {
+    fn get_hand_type_no_jokers(cards:&[char]) -> HandType {
         let mut frequency_vector:[u8;u8::MAX as usize] = [0;u8::MAX as usize];
         for i in 0..cards.len() {
             frequency_vector[cards[i] as usize] += 1;
@@ -98,23 +113,23 @@ impl Hand {
         let pairs = frequency_vector.iter().filter(|v| **v == 2).count();
 
         if fives == 1 {
-            FIVE
+            Five
         } else if fours == 1 {
-            FOUR
+            Four
         } else if threes == 1 && pairs == 1 {
             FullHouse
         } else if threes == 1 {
-            THREE
+            Three
         } else if pairs == 2 {
             TwoPairs
         } else if pairs == 1 {
-            PAIR
+            Pair
         } else {
             HighCard
         }
     }
 
-    fn get_hand_type(cards:&Vec<char>, part2: bool) -> HandType {
+    fn get_hand_type(cards:&[char], part2: bool) -> HandType {
 
         let cards_no_jokers:Vec<char> = cards.iter().filter(|c| **c != 'J').copied().collect();
         let num_jokers = if part2 {
@@ -123,7 +138,7 @@ impl Hand {
             0
         };
 
-        return if num_jokers == 0 {
+        if num_jokers == 0 {
             Hand::get_hand_type_no_jokers(cards)
         } else {
             // Get hand type with no jokers
@@ -131,79 +146,95 @@ impl Hand {
 
             if num_jokers == 1 {
                 match hand_type_base {
-                    FullHouse => FOUR,
-                    THREE => FOUR,
+                    FullHouse => Four,
+                    Three => Four,
                     TwoPairs => FullHouse,
-                    PAIR => THREE,
-                    HighCard => PAIR,
-                    _ => FIVE,
+                    Pair => Three,
+                    HighCard => Pair,
+                    _ => Five,
                 }
             } else if num_jokers == 2 {
                 match hand_type_base {
-                    TwoPairs => FOUR,
-                    PAIR => FOUR,
-                    HighCard => THREE,
-                    _ => FIVE,
+                    TwoPairs => Four,
+                    Pair => Four,
+                    HighCard => Three,
+                    _ => Five,
                 }
             } else if num_jokers == 3 {
                 match hand_type_base {
-                    HighCard => FOUR,
-                    _ => FIVE,
+                    HighCard => Four,
+                    _ => Five,
                 }
             } else {
-                FIVE
+                Five
             }
         }
     }
 }
 
-fn play_game(mut hands:Vec<Hand>) -> usize {
-    hands.sort();
+fn play_game(hands:&[Hand], part2:bool) -> usize {
+    let mut hands:Vec<&Hand> = hands.iter().collect();
+    hands.sort_by_cached_key(|hand| hand.strength(part2));
     hands.iter().enumerate()
         .map(|(rank, hand)| (rank + 1) * hand.bid as usize)
         .sum::<usize>()
 }
-fn part1(lines : Vec<&str>) -> String {
-    play_game(lines.iter().map(|line| Hand::new(line, false)).collect()).to_string()
+
+pub fn parse(input:&str) -> Result<Vec<Hand>> {
+    input.lines()
+        .enumerate()
+        .map(|(index, line)| Hand::new(index, line))
+        .collect()
+}
+
+fn part1(hands:&[Hand]) -> Result<String> {
+    Ok(play_game(hands, false).to_string())
 }
 
-fn part2(lines : Vec<&str>) -> String {
-    play_game(lines.iter().map(|line| Hand::new(line, true)).collect()).to_string()
+fn part2(hands:&[Hand]) -> Result<String> {
+    Ok(play_game(hands, true).to_string())
 }
 
 
+const EXAMPLE:&str = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
+
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "6440"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "5905"},
+];
+
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
 
 
-    const INPUT:&str = "32T3K 765
-T55J5 684
-KK677 28
-KTJJT 220
-QQQJA 483";
-
     #[test]
     fn test1() {
-        assert_eq!("6440", solve(INPUT.to_string(), Part1));
+        assert_eq!("6440", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_07.txt");
-        assert_eq!("248569531", solve(input.to_string(), Part1));
+        assert_answer(7, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("5905", solve(INPUT.to_string(), Part2));
+        assert_eq!("5905", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_07.txt");
-        assert_eq!("250382098", solve(input.to_string(), Part2));
+        assert_answer(7, Part2, solve(input.to_string(), Part2));
     }
 }
@@ -1,99 +1,217 @@
-use std::collections::HashMap;
-use super::Part;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use super::math::Congruence;
+use super::{Example, Part, Puzzle, Result, SolveError, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Network;
+
+    fn day(&self) -> u8 {
+        8
+    }
+
+    fn title(&self) -> &'static str {
+        "Haunted Wasteland"
+    }
+
+    fn parse(&self, input:&str) -> Result<Network> {
+        parse(input)
+    }
+
+    fn part1(&self, network:&Network) -> Result<String> {
+        part1(network)
+    }
+
+    fn part2(&self, network:&Network) -> Result<String> {
+        part2(network)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
     }
 }
 
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
 
-fn parse_map(mut lines: Vec<&str>) -> (Vec<char>, HashMap<&str, (&str, &str)>) {
-    let instructions:Vec<char> = lines.remove(0).chars().collect();
+// The left/right instructions and the left and right node of every node
+#[derive(Debug)]
+pub struct Network {
+    pub instructions:Vec<char>,
+    pub nodes:HashMap<String, (String, String)>,
+}
+
+pub fn parse(input:&str) -> Result<Network> {
+    let lines:Vec<&str> = input.lines().collect();
+    let first = lines.first().copied().unwrap_or("");
+    let instructions:Vec<char> = first.chars().collect();
+    if instructions.is_empty() {
+        return Err(SolveError::malformed(1, 1, "missing instructions"));
+    } else if let Some(column) = instructions.iter().position(|c| *c != 'L' && *c != 'R') {
+        return Err(SolveError::malformed(1, column + 1, format!("unexpected instruction '{}'", instructions[column])));
+    }
 
     let mut map = HashMap::new();
-    let list = lines.iter()
-        .filter( |s| s.contains("="))
-        .map(|line| {
-            let items:Vec<&str> = line.split(|c:char| c == ' ' || c == '|' || c == ',' || c == '(' || c == ')')
-                .filter(|s| s.starts_with(|c:char| c.is_alphanumeric()))
-                .collect();
-            items
-        })
-        .collect::<Vec<Vec<&str>>>();
+    for (index, line) in lines.iter().enumerate().skip(1) {
+        if !line.contains('=') {
+            continue;
+        }
+
+        let items:Vec<&str> = line.split([' ', '|', ',', '(', ')'])
+            .filter(|s| s.starts_with(|c:char| c.is_alphanumeric()))
+            .collect();
+
+        if items.len() != 3 {
+            return Err(SolveError::malformed(index + 1, 1, "expected 'NODE = (LEFT, RIGHT)'"));
+        }
 
-    for item in list {
-        map.insert(item[0], (item[1], item[2]));
+        map.insert(items[0].to_string(), (items[1].to_string(), items[2].to_string()));
     }
 
-    (instructions, map)
+    Ok(Network{instructions, nodes: map})
 }
 
-fn traverse_path<F>(instructions:&Vec<char>, map:&HashMap<&str, (&str, &str)>, start_pos:&str, end_condition: F) -> usize
+/// A walk from one node, following the instructions. After `prefix + length` steps it is back at the
+/// node and instruction it was at after `prefix` steps, so from then on it repeats every `length`
+/// steps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub prefix:usize,
+    pub length:usize,
+    /// Steps before `prefix + length` at which the walk is on an end node, in order
+    pub ends:Vec<usize>,
+}
+
+impl Cycle {
+    /// Whether the walk is on an end node after `steps` steps
+    pub fn is_end_at(&self, steps:usize) -> bool {
+        let step = if steps < self.prefix { steps } else { self.prefix + (steps - self.prefix) % self.length };
+        self.ends.binary_search(&step).is_ok()
+    }
+
+    pub fn first_end(&self) -> Option<usize> {
+        self.ends.first().copied()
+    }
+
+    // Ends that repeat, as steps into the cycle
+    fn cycle_ends(&self) -> impl Iterator<Item=usize> + '_ {
+        let prefix = self.prefix;
+        self.ends.iter().filter(move |step| **step >= prefix).map(move |step| step - prefix)
+    }
+}
+
+/// The walk from `start_pos`, followed until it is at a node at the same point in the instructions
+/// as before
+pub fn traverse_path<F>(network:&Network, start_pos:&str, end_condition: F) -> Result<Cycle>
     where
         F: Fn(&str) -> bool,
 {
+    let instructions = &network.instructions;
+    let mut visited:HashMap<(&str, usize), usize> = HashMap::new();
+    let mut ends = vec![];
     let mut pos = start_pos;
     let mut steps = 0;
-    while !end_condition(pos) {
-        let dir = instructions[steps % instructions.len()];
-        let step = map.get(pos).unwrap();
-        pos = if dir == 'L' {
-            step.0
-        } else {
-            step.1
-        };
+    loop {
+        if let Some(prefix) = visited.insert((pos, steps % instructions.len()), steps) {
+            return Ok(Cycle{prefix, length: steps - prefix, ends});
+        }
+        if end_condition(pos) {
+            ends.push(steps);
+        }
+
+        pos = next_node(network, pos, steps)?;
         steps += 1;
     }
+}
 
-    steps
+/// Steps from `start_pos` to the first node `end_condition` accepts, stopping there so the nodes
+/// after it don't need to be known
+pub fn steps_to_end<F>(network:&Network, start_pos:&str, end_condition: F) -> Result<usize>
+    where
+        F: Fn(&str) -> bool,
+{
+    let mut visited = HashSet::new();
+    let mut pos = start_pos;
+    let mut steps = 0;
+    while !end_condition(pos) {
+        if !visited.insert((pos, steps % network.instructions.len())) {
+            return Err(SolveError::unsolvable(format!("path from {} never reaches an end node", start_pos)));
+        }
+        pos = next_node(network, pos, steps)?;
+        steps += 1;
+    }
+    Ok(steps)
 }
 
-fn gcd(a: usize, b: usize) -> usize {
-    if b == 0 {
-        a
+// The node after `pos`, following the instruction for step `steps`
+fn next_node<'a>(network:&'a Network, pos:&str, steps:usize) -> Result<&'a str> {
+    let instructions = &network.instructions;
+    let dir = instructions[steps % instructions.len()];
+    let step = network.nodes.get(pos)
+        .ok_or_else(|| SolveError::unsolvable(format!("unknown node {}", pos)))?;
+    Ok(if dir == 'L' {
+        &step.0
     } else {
-        gcd(b, a % b)
-    }
+        &step.1
+    })
 }
 
-fn lcm(numbers: &[usize]) -> usize {
-    let mut result = numbers[0];
+/// First step at which every walk is on an end node.
+///
+/// Before the last walk reaches its cycle every step is tried. From then on each walk is on an end
+/// node at steps some cycle end more than a multiple of its cycle length. Walk by walk, the steps the
+/// walks so far share are combined with each cycle end of the next by the Chinese remainder theorem,
+/// dropping the combinations that contradict each other and keeping each distinct one once.
+pub fn first_common_end(cycles:&[Cycle]) -> Result<usize> {
+    let start = cycles.iter().map(|cycle| cycle.prefix).max().unwrap_or(0);
+    if let Some(steps) = (0..start).find(|steps| cycles.iter().all(|cycle| cycle.is_end_at(*steps))) {
+        return Ok(steps);
+    }
 
-    for &num in &numbers[1..] {
-        result = (result * num) / gcd(result, num);
+    let too_large = || SolveError::unsolvable("the step the ghosts line up on is too large");
+    let mut shared = HashSet::from([Congruence::new(0, 1)]);
+    for cycle in cycles {
+        let mut next = HashSet::new();
+        for steps in shared.iter() {
+            for end in cycle.cycle_ends() {
+                let ends = Congruence::new((cycle.prefix + end) as i128, cycle.length as i128);
+                next.extend(steps.checked_merge(ends).map_err(|_| too_large())?);
+            }
+        }
+        shared = next;
     }
 
-    result
+    let steps = shared.iter()
+        .map(|steps| steps.first_at_least(start as i128).map_err(|_| too_large()))
+        .collect::<Result<Vec<i128>>>()?
+        .into_iter()
+        .min()
+        .ok_or_else(|| SolveError::unsolvable("the ghosts are never all on end nodes at the same time"))?;
+    usize::try_from(steps).map_err(|_| too_large())
 }
 
-fn part1(lines : Vec<&str>) -> String {
-    let (instructions, map) = parse_map(lines);
-    traverse_path(&instructions, &map, "AAA", |s| s.eq("ZZZ")).to_string()
+fn part1(network:&Network) -> Result<String> {
+    Ok(steps_to_end(network, "AAA", |s| s.eq("ZZZ"))?.to_string())
 }
 
-fn part2(lines : Vec<&str>) -> String {
-    let (instructions, map) = parse_map(lines);
+fn part2(network:&Network) -> Result<String> {
+    let cycles:Vec<Cycle> = network.nodes.keys()
+        .filter(|s| s.ends_with('A'))
+        .map(|pos| traverse_path(network, pos, |s| s.ends_with('Z')))
+        .collect::<Result<Vec<Cycle>>>()?;
 
-    let path_lengths:Vec<usize> = map.keys()
-        .filter(|s| s.ends_with("A"))
-        .map(|pos| traverse_path(&instructions, &map, pos, |s| s.ends_with("Z")))
-        .collect();
+    if cycles.is_empty() {
+        return Err(SolveError::unsolvable("no start nodes ending with A"));
+    }
 
-    lcm(path_lengths.as_slice()).to_string()
+    Ok(first_common_end(&cycles)?.to_string())
 }
 
-#[cfg(test)]
-mod tests {
-    use std::assert_eq;
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-    use util::Part::{Part1, Part2};
-
 
-    const INPUT:&str = "RL
+const EXAMPLE:&str = "RL
 
 AAA = (BBB, CCC)
 BBB = (DDD, EEE)
@@ -103,13 +221,13 @@ EEE = (EEE, EEE)
 GGG = (GGG, GGG)
 ZZZ = (ZZZ, ZZZ)";
 
-    const INPUT2:&str = "LLR
+const EXAMPLE_2:&str = "LLR
 
 AAA = (BBB, BBB)
 BBB = (AAA, ZZZ)
 ZZZ = (ZZZ, ZZZ)";
 
-    const INPUT3:&str  = "LR
+const EXAMPLE_3:&str = "LR
 
 11A = (11B, XXX)
 11B = (XXX, 11Z)
@@ -120,30 +238,97 @@ ZZZ = (ZZZ, ZZZ)";
 22Z = (22B, 22B)
 XXX = (XXX, XXX)";
 
+// The ghosts reach their first end nodes after 2 and 1 steps, but only line up at step 5
+const EXAMPLE_OFFSET:&str = "L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11C, 11C)
+11C = (11B, 11B)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22Z, 22Z)";
+
+const EXAMPLES:[Example;4] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "2"},
+    Example{part: Part::Part1, input: EXAMPLE_2, expected: "6"},
+    Example{part: Part::Part2, input: EXAMPLE_3, expected: "6"},
+    Example{part: Part::Part2, input: EXAMPLE_OFFSET, expected: "5"},
+];
+
+
+#[cfg(test)]
+mod tests {
+    use std::assert_eq;
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
+
+
     #[test]
     fn test1() {
-        assert_eq!("2", solve(INPUT.to_string(), Part1));
+        assert_eq!("2", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test11() {
-        assert_eq!("6", solve(INPUT2.to_string(), Part1));
+        assert_eq!("6", solve(EXAMPLE_2.to_string(), Part1).unwrap());
+    }
+
+    #[test]
+    fn test_steps_to_end() {
+        // Nothing needs to be known about the nodes after ZZZ
+        assert_eq!("1", solve("R\n\nAAA = (ZZZ, ZZZ)".to_string(), Part1).unwrap());
+        let network = parse(EXAMPLE_2).unwrap();
+        assert_eq!(Ok(0), steps_to_end(&network, "ZZZ", |s| s.eq("ZZZ")));
+        assert_eq!(Err(SolveError::unsolvable("path from AAA never reaches an end node")), steps_to_end(&network, "AAA", |s| s.eq("CCC")));
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_08.txt");
-        assert_eq!("17621", solve(input.to_string(), Part1));
+        assert_answer(8, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("6", solve(INPUT3.to_string(), Part2));
+        assert_eq!("6", solve(EXAMPLE_3.to_string(), Part2).unwrap());
+    }
+
+    #[test]
+    fn test_traverse_path() {
+        let network = parse(EXAMPLE_3).unwrap();
+        let is_end = |s:&str| s.ends_with('Z');
+        assert_eq!(Cycle{prefix: 1, length: 2, ends: vec![2]}, traverse_path(&network, "11A", is_end).unwrap());
+        let cycle = traverse_path(&network, "22A", is_end).unwrap();
+        assert_eq!(Cycle{prefix: 1, length: 6, ends: vec![3, 6]}, cycle);
+        assert_eq!(vec![3, 6, 9, 12], (0..14).filter(|steps| cycle.is_end_at(*steps)).collect::<Vec<usize>>());
+        assert_eq!(Err(SolveError::unsolvable("unknown node YYY")), traverse_path(&parse("L\n\nAAA = (YYY, YYY)").unwrap(), "AAA", is_end));
+    }
+
+    #[test]
+    fn test_first_common_end() {
+        assert_eq!("5", solve(EXAMPLE_OFFSET.to_string(), Part2).unwrap());
+        // Lined up before the second walk reaches its cycle
+        let early = [Cycle{prefix: 0, length: 2, ends: vec![1]}, Cycle{prefix: 4, length: 3, ends: vec![3, 5]}];
+        assert_eq!(Ok(3), first_common_end(&early));
+        // Ends at odd and at even steps only
+        let apart = [Cycle{prefix: 0, length: 2, ends: vec![1]}, Cycle{prefix: 0, length: 4, ends: vec![0, 2]}];
+        assert_eq!(Err(SolveError::unsolvable("the ghosts are never all on end nodes at the same time")), first_common_end(&apart));
+        // Every choice of end within the cycles is tried
+        let choices = [Cycle{prefix: 0, length: 6, ends: vec![1, 4]}, Cycle{prefix: 0, length: 5, ends: vec![2, 4]}];
+        assert_eq!(Ok(4), first_common_end(&choices));
+        // 10^40 choices, but only ten distinct ways for the walks to line up
+        let many = (0..40).map(|_| Cycle{prefix: 0, length: 10, ends: (0..10).collect()}).collect::<Vec<Cycle>>();
+        assert_eq!(Ok(0), first_common_end(&many));
+        let later = (0..40).map(|walk| Cycle{prefix: 0, length: 10, ends: (1..10).filter(|end| *end != walk % 9 + 1).collect()}).collect::<Vec<Cycle>>();
+        assert_eq!(Err(SolveError::unsolvable("the ghosts are never all on end nodes at the same time")), first_common_end(&later));
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_08.txt");
-        assert_eq!("20685524831999", solve(input.to_string(), Part2));
+        assert_answer(8, Part2, solve(input.to_string(), Part2));
     }
 }
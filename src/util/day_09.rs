@@ -1,13 +1,39 @@
-use super::Part;
+use super::{parse_token, Example, Part, Puzzle, Result, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Vec<Vec<i32>>;
+
+    fn day(&self) -> u8 {
+        9
+    }
+
+    fn title(&self) -> &'static str {
+        "Mirage Maintenance"
+    }
+
+    fn parse(&self, input:&str) -> Result<Vec<Vec<i32>>> {
+        parse(input)
+    }
+
+    fn part1(&self, rows:&Vec<Vec<i32>>) -> Result<String> {
+        part1(rows)
+    }
+
+    fn part2(&self, rows:&Vec<Vec<i32>>) -> Result<String> {
+        part2(rows)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
     }
 }
 
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
+
 fn calc_next_value(mut numbers:Vec<i32>, part2:bool) -> Vec<i32> {
     let mut history:Vec<Vec<i32>> = vec![];
 
@@ -45,56 +71,70 @@ fn calc_next_value(mut numbers:Vec<i32>, part2:bool) -> Vec<i32> {
     history.remove(0)
 }
 
-fn calc_sum_of_extrapolated_values(lines : Vec<&str>, part2:bool) -> i32 {
-    lines.iter()
-        .map(|s| s.split(' ')
-                .map(|s| s.parse::<i32>().unwrap())
-                .collect::<Vec<i32>>())
-        .map(|row| calc_next_value(row, part2))
+// One history of readings per line
+pub fn parse(input:&str) -> Result<Vec<Vec<i32>>> {
+    input.lines()
+        .enumerate()
+        .map(|(index, line)| line.split(' ')
+                .map(|s| parse_token::<i32>(index, line, s))
+                .collect::<Result<Vec<i32>>>())
+        .collect()
+}
+
+fn calc_sum_of_extrapolated_values(rows:&[Vec<i32>], part2:bool) -> i32 {
+    rows.iter()
+        .map(|row| calc_next_value(row.clone(), part2))
         .map(|row| *row.last().unwrap())
         .sum::<i32>()
 }
 
-fn part1(lines : Vec<&str>) -> String {
-    calc_sum_of_extrapolated_values(lines, false).to_string()
+fn part1(rows:&[Vec<i32>]) -> Result<String> {
+    Ok(calc_sum_of_extrapolated_values(rows, false).to_string())
 }
 
-fn part2(lines : Vec<&str>) -> String {
-    calc_sum_of_extrapolated_values(lines, true).to_string()
+fn part2(rows:&[Vec<i32>]) -> Result<String> {
+    Ok(calc_sum_of_extrapolated_values(rows, true).to_string())
 }
 
 
+const EXAMPLE:&str = "0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45";
+
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "114"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "2"},
+];
+
+
 #[cfg(test)]
 mod tests {
     use std::assert_eq;
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
 
 
-    const INPUT:&str = "0 3 6 9 12 15
-1 3 6 10 15 21
-10 13 16 21 30 45";
-
     #[test]
     fn test1() {
-        assert_eq!("114", solve(INPUT.to_string(), Part1));
+        assert_eq!("114", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_09.txt");
-        assert_eq!("2008960228", solve(input.to_string(), Part1));
+        assert_answer(9, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("2", solve(INPUT.to_string(), Part2));
+        assert_eq!("2", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_09.txt");
-        assert_eq!("1097", solve(input.to_string(), Part2));
+        assert_answer(9, Part2, solve(input.to_string(), Part2));
     }
 }
@@ -1,174 +1,133 @@
 use std::collections::{HashSet, VecDeque};
 use std::hash::Hash;
-use util::day_10::Direction::{DOWN, LEFT, NotAvail, RIGHT, UP};
-use super::Part;
-
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+use super::grid::{Direction, Grid, Pos};
+use super::grid::Direction::{Down, Left, Right, Up};
+use super::{Example, Part, Puzzle, Result, SolveError, Solver};
+
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Grid<Tile>;
+
+    fn day(&self) -> u8 {
+        10
     }
-}
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
-enum Direction {
-    LEFT,
-    RIGHT,
-    UP,
-    DOWN,
-    NotAvail,
-}
+    fn title(&self) -> &'static str {
+        "Pipe Maze"
+    }
 
-impl Direction {
-    fn get_opposite(&self) -> Direction {
-        match self {
-            LEFT => RIGHT,
-            RIGHT => LEFT,
-            UP => DOWN,
-            DOWN => UP,
-            NotAvail => NotAvail,
-        }
+    fn parse(&self, input:&str) -> Result<Grid<Tile>> {
+        parse(input)
     }
 
-    fn get_delta(&self) -> (i32,i32) {
-        match *self {
-            LEFT => (-1,0),
-            RIGHT => (1,0),
-            UP => (0,-1),
-            DOWN => (0,1),
-            NotAvail => panic!("..."),
-        }
+    fn part1(&self, map:&Grid<Tile>) -> Result<String> {
+        part1(map)
     }
 
+    fn part2(&self, map:&Grid<Tile>) -> Result<String> {
+        part2(map)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
+    }
 }
 
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
 
 #[derive(Debug, Eq, PartialEq, Hash)]
-struct Tile {
-    ch:char,
-    entry_directions: Vec<Direction>,
-    exit_directions: Vec<Direction>,
+pub struct Tile {
+    pub ch:char,
+    pub entry_directions: Vec<Direction>,
+    pub exit_directions: Vec<Direction>,
 }
 
 impl Tile {
-    fn new(ch:char) -> Tile {
+    fn new(ch:char) -> Option<Tile> {
         let entry_directions = match ch {
-            '-' => vec![LEFT,RIGHT],
-            '|' => vec![UP,DOWN],
-            'L' => vec![DOWN, LEFT],
-            'J' => vec![DOWN, RIGHT],
-            '7' => vec![UP, RIGHT],
-            'F' => vec![UP, LEFT],
-            'S' => vec![UP,DOWN],
-            '.' => vec![NotAvail],
-            _ => {
-                println!("char={}", ch);
-                panic!("...");
-            },
+            '-' => vec![Left,Right],
+            '|' => vec![Up,Down],
+            'L' => vec![Down, Left],
+            'J' => vec![Down, Right],
+            '7' => vec![Up, Right],
+            'F' => vec![Up, Left],
+            'S' => vec![Up,Down],
+            '.' => vec![],
+            _ => return None,
         };
 
-        let exit_directions = entry_directions.iter().map(|dir| dir.get_opposite()).collect();
-        Tile{ch,entry_directions, exit_directions}
+        let exit_directions = entry_directions.iter().map(|dir| dir.opposite()).collect();
+        Some(Tile{ch,entry_directions, exit_directions})
     }
 
     fn matches(&self, exit_dir:&Direction, other:&Tile) -> bool {
-        for  entry_dir in other.entry_directions.iter() {
-            if *entry_dir == NotAvail {
-                break;
-            } else if *exit_dir == *entry_dir {
-                return true;
-            }
-        }
-
-        return false;
+        other.entry_directions.contains(exit_dir)
     }
 
 }
 
-fn next_pos(x:i32, y:i32, map:&Vec<Vec<Tile>>) -> Vec<(i32,i32)> {
-    let current_tile = map.get(y as usize).unwrap().get(x as usize).unwrap();
-   let mut next_steps = vec![];
-
+fn next_pos(pos:Pos, map:&Grid<Tile>) -> Vec<Pos> {
+    let current_tile = &map[pos];
 
-    for dir in current_tile.exit_directions.iter() {
-        let (d_x, d_y) = dir.get_delta();
-        let next_y = d_y + y;
-        let next_x = d_x + x;
-
-        if next_y < 0 || next_y >= map.len() as i32 || next_x < 0 || next_x >= map.first().unwrap().len() as i32 {
-            continue;
-        }
-
-        let next_tile = map.get(next_y as usize).unwrap().get(next_x as usize).unwrap();
-
-        if current_tile.matches(dir, next_tile) {
-            next_steps.push((next_x, next_y));
-        }
-    }
-
-    next_steps
+    current_tile.exit_directions.iter()
+        .filter_map(|dir| map.step(pos, *dir).map(|next| (dir, next)))
+        .filter(|(dir, next)| current_tile.matches(dir, &map[*next]))
+        .map(|(_, next)| next)
+        .collect()
 }
 
-fn parse_map(lines:Vec<&str>) -> Vec<Vec<Tile>> {
-    lines.iter()
-        .map(|row| row.chars().into_iter()
-            .map(|c| Tile::new(c)).collect::<Vec<Tile>>())
-        .collect()
+pub fn parse(input:&str) -> Result<Grid<Tile>> {
+    Grid::parse(input, Tile::new, "expected '|', '-', 'L', 'J', '7', 'F', '.' or 'S'")
 }
 
-fn get_pipe(map:&Vec<Vec<Tile>>) -> Vec<(i32,i32)> {
-    let (start_x, start_y) = map.iter().enumerate()
-        .map( |(y, row)| row.iter().enumerate()
-            .map(move |(x, tile)| (x,y, tile)))
-        .flatten()
-        .find(|(_,_, tile)| tile.ch == 'S')
-        .map(|(x,y,_)| (x as i32, y as i32))
-        .unwrap();
+fn get_pipe(map:&Grid<Tile>) -> Result<Vec<Pos>> {
+    let start = map.find(|tile| tile.ch == 'S')
+        .ok_or_else(|| SolveError::malformed(1, 1, "missing start tile 'S'"))?;
 
     let mut visited = HashSet::new();
     let mut stack = VecDeque::new();
     let mut pipe = vec![];
 
-    visited.insert((start_x, start_y));
-    pipe.push((start_x, start_y));
+    visited.insert(start);
+    pipe.push(start);
     // Start in one direction
-    let (first_step_x, first_step_y) = *next_pos(start_x, start_y, &map).first().unwrap();
-    stack.push_back((1, first_step_x, first_step_y));
+    let first_step = *next_pos(start, map).first()
+        .ok_or_else(|| SolveError::unsolvable("no pipe connects to the start tile"))?;
+    stack.push_back((1, first_step));
 
     // Iterate until we reach start position
     while !stack.is_empty() {
-        let (steps, curr_x, curr_y) = stack.pop_front().unwrap();
-        visited.insert((curr_x, curr_y));
-        pipe.push((curr_x, curr_y));
+        let (steps, curr) = stack.pop_front().unwrap();
+        visited.insert(curr);
+        pipe.push(curr);
 
-        let next_steps = next_pos(curr_x, curr_y, &map);
-        for (next_x, next_y) in next_steps {
-            if visited.contains(&(next_x, next_y)) {
+        for next in next_pos(curr, map) {
+            if visited.contains(&next) {
                 // Already visited
                 continue;
             } else {
                 // Visit new node
-                stack.push_back((steps+1, next_x, next_y));
+                stack.push_back((steps+1, next));
             }
         }
     }
 
-    pipe
+    Ok(pipe)
 }
 
 
 
-fn part1(lines : Vec<&str>) -> String {
-    let map = parse_map(lines);
-    let pipe = get_pipe(&map);
-    ((pipe.len()+1)/2).to_string()
+fn part1(map:&Grid<Tile>) -> Result<String> {
+    let pipe = get_pipe(map)?;
+    Ok(pipe.len().div_ceil(2).to_string())
 }
 
 
-fn part2(lines : Vec<&str>) -> String {
-    let map = parse_map(lines);
-    let mut pipe:VecDeque<(i32, i32)> = get_pipe(&map).into_iter().collect();
+fn part2(map:&Grid<Tile>) -> Result<String> {
+    let mut pipe:VecDeque<Pos> = get_pipe(map)?.into_iter().collect();
     let mut visited = HashSet::new();
     pipe.iter().copied().for_each( |item| {
         visited.insert(item);
@@ -186,91 +145,39 @@ fn part2(lines : Vec<&str>) -> String {
     }
 
     let mut first= true;
-    let mut current_direction = Direction::DOWN;
-    let mut current_inside_direction = Direction::RIGHT;
-    let mut curr_x = pipe.front().unwrap().0;
-    let mut curr_y = pipe.pop_front().unwrap().1;
+    let mut current_direction = Down;
+    let mut current_inside_direction = Right;
+    let mut curr = pipe.pop_front().unwrap();
     let mut filled_positions = HashSet::new();
 
 
     while !pipe.is_empty() {
-        let (next_x,next_y) = pipe.pop_front().unwrap();
-        let next_dir = if curr_x == next_x {
-            if next_y < curr_y {
-                UP
-            } else {
-                DOWN
-            }
-        } else if next_x < curr_x {
-            LEFT
-        } else {
-            RIGHT
-        };
+        let next = pipe.pop_front().unwrap();
+        let next_dir = *Direction::ALL.iter()
+            .find(|dir| map.step(curr, **dir) == Some(next))
+            .ok_or_else(|| SolveError::unsolvable("loop jumps between tiles that are not next to each other"))?;
 
         if first {
-            if next_dir == DOWN {
-                current_direction = DOWN;
-                current_inside_direction = RIGHT;
-            } else if next_dir == RIGHT {
-                current_direction = RIGHT;
-                current_inside_direction = DOWN;
+            if next_dir == Down {
+                current_direction = Down;
+                current_inside_direction = Right;
+            } else if next_dir == Right {
+                current_direction = Right;
+                current_inside_direction = Down;
             } else {
-                panic!("unexp..");
+                return Err(SolveError::unsolvable("loop does not leave its top-left corner down or right"));
             }
             first = false;
          }
 
-        let mut next_inside_direction = current_inside_direction;
-
-        // Check if we are turning
-        if current_direction != next_dir {
-            let turn = if current_direction == LEFT {
-                if next_dir == UP {
-                    RIGHT
-                } else {
-                    LEFT
-                }
-            } else if current_direction == RIGHT {
-                if next_dir == DOWN {
-                    RIGHT
-                } else {
-                    LEFT
-                }
-            } else if current_direction == UP {
-                if next_dir == LEFT {
-                    LEFT
-                } else {
-                    RIGHT
-                }
-            } else if current_direction == DOWN {
-                if next_dir == LEFT {
-                    RIGHT
-                } else {
-                    LEFT
-                }
-            } else {
-                panic!("...");
-            };
-
-            // Update inside direction
-            next_inside_direction = if turn == LEFT {
-                match current_inside_direction {
-                    LEFT => DOWN,
-                    RIGHT => UP,
-                    UP => LEFT,
-                    DOWN => RIGHT,
-                    NotAvail => panic!(",,"),
-                }
-            } else {
-                match current_inside_direction {
-                    LEFT => UP,
-                    RIGHT => DOWN,
-                    UP => RIGHT,
-                    DOWN => LEFT,
-                    NotAvail => panic!(",,"),
-                }
-            };
-        }
+        // Turning right or left turns the inside the same way
+        let next_inside_direction = if current_direction == next_dir {
+            current_inside_direction
+        } else if next_dir == current_direction.turn_right() {
+            current_inside_direction.turn_right()
+        } else {
+            current_inside_direction.turn_left()
+        };
 
         // Fill up inside positions
         let inside_directions = if current_inside_direction == next_inside_direction {
@@ -280,32 +187,24 @@ fn part2(lines : Vec<&str>) -> String {
         };
 
         for inside_dir in inside_directions {
-            // Fill position
-            let fill_pos = match inside_dir {
-                UP => (curr_x, curr_y - 1),
-                DOWN => (curr_x, curr_y +1),
-                LEFT => (curr_x - 1, curr_y),
-                RIGHT => (curr_x + 1, curr_y),
-                _ => panic!("..."),
-            };
-
             // try to fill and all its neighbors
-            fill(fill_pos, &mut visited, &mut filled_positions);
+            if let Some(fill_pos) = map.step(curr, inside_dir) {
+                fill(map, fill_pos, &visited, &mut filled_positions);
+            }
         }
 
 
         // Increment to next position
-        curr_x = next_x;
-        curr_y = next_y;
+        curr = next;
         current_direction = next_dir;
         current_inside_direction = next_inside_direction;
 
     }
 
-    filled_positions.len().to_string()
+    Ok(filled_positions.len().to_string())
 }
 
-fn fill(pos:(i32,i32), visited: &mut HashSet<(i32, i32)>, fill:&mut HashSet<(i32, i32)>) {
+fn fill(map:&Grid<Tile>, pos:Pos, visited: &HashSet<Pos>, fill:&mut HashSet<Pos>) {
     let mut stack = VecDeque::new();
     stack.push_back(pos);
 
@@ -319,43 +218,30 @@ fn fill(pos:(i32,i32), visited: &mut HashSet<(i32, i32)>, fill:&mut HashSet<(i32
         fill.insert(item);
 
         // add all adjacent nodes
-        for (dx, dy) in [(0,1),(0,-1),(1,0),(-1,0)] {
-            let adjacent = (item.0 + dx, item.1 + dy);
-            stack.push_back(adjacent);
-        }
+        map.neighbors4(item).for_each(|adjacent| stack.push_back(adjacent));
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use std::assert_eq;
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-    use util::Part::{Part1, Part2};
-
-
 
-    const INPUT_2:&str = "-L|F7
+const EXAMPLE:&str = "-L|F7
 7S-7|
 L|7||
 -L-J|
 L|-JF";
 
-
-
-    const INPUT_4:&str = "7-F7-
+const EXAMPLE_2:&str = "7-F7-
 .FJ|7
 SJLL7
 |F--J
 LJ.LJ";
 
-    const INPUT:&str = ".....
+const EXAMPLE_3:&str = ".....
 .S-7.
 .|.|.
 .L-J.
 .....";
 
-    const INPUT_5:&str = "...........
+const EXAMPLE_4:&str = "...........
 .S-------7.
 .|F-----7|.
 .||.....||.
@@ -365,7 +251,7 @@ LJ.LJ";
 .L--J.L--J.
 ...........";
 
-    const INPUT_6:&str = ".F----7F7F7F7F-7....
+const EXAMPLE_5:&str = ".F----7F7F7F7F-7....
 .|F--7||||||||FJ....
 .||.FJ||||||||L7....
 FJL7L7LJLJ||LJ.L-7..
@@ -376,9 +262,7 @@ L--J.L7...LJS7F-7L7.
 ....FJL-7.||.||||...
 ....L---J.LJ.LJLJ...";
 
-
-
-    const INPUT_8:&str = "FF7FSF7F7F7F7F7F---7
+const EXAMPLE_6:&str = "FF7FSF7F7F7F7F7F---7
 L|LJ||||||||||||F--J
 FL-7LJLJ||||||LJL-77
 F--JF--7||LJLJ7F7FJ-
@@ -389,47 +273,65 @@ L---JF-JLJ.||-FJLJJ7
 L.L7LFJ|||||FJL7||LJ
 L7JLJL-JLJLJL--JLJ.L";
 
+const EXAMPLES:[Example;6] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "4"},
+    Example{part: Part::Part1, input: EXAMPLE_2, expected: "8"},
+    Example{part: Part::Part2, input: EXAMPLE_3, expected: "1"},
+    Example{part: Part::Part2, input: EXAMPLE_4, expected: "4"},
+    Example{part: Part::Part2, input: EXAMPLE_5, expected: "8"},
+    Example{part: Part::Part2, input: EXAMPLE_6, expected: "10"},
+];
+
+
+#[cfg(test)]
+mod tests {
+    use std::assert_eq;
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
+
+
     #[test]
     fn test1() {
-        assert_eq!("4", solve(INPUT_2.to_string(), Part1));
+        assert_eq!("4", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test12() {
-        assert_eq!("8", solve(INPUT_4.to_string(), Part1));
+        assert_eq!("8", solve(EXAMPLE_2.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_10.txt");
-        assert_eq!("6870", solve(input.to_string(), Part1));
+        assert_answer(10, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("1", solve(INPUT.to_string(), Part2));
+        assert_eq!("1", solve(EXAMPLE_3.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test21() {
-        assert_eq!("4", solve(INPUT_5.to_string(), Part2));
+        assert_eq!("4", solve(EXAMPLE_4.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test22() {
-        assert_eq!("8", solve(INPUT_6.to_string(), Part2));
+        assert_eq!("8", solve(EXAMPLE_5.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test23() {
-        assert_eq!("10", solve(INPUT_8.to_string(), Part2));
+        assert_eq!("10", solve(EXAMPLE_6.to_string(), Part2).unwrap());
     }
 
 
-
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_10.txt");
-        assert_eq!("287", solve(input.to_string(), Part2));
+        assert_answer(10, Part2, solve(input.to_string(), Part2));
     }
 }
@@ -1,56 +1,67 @@
 use std::cmp::{max, min};
-use super::Part;
+use super::grid::{Grid, Pos};
+use super::{Example, Part, Puzzle, Result, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Grid<char>;
+
+    fn day(&self) -> u8 {
+        11
+    }
+
+    fn title(&self) -> &'static str {
+        "Cosmic Expansion"
+    }
+
+    fn parse(&self, input:&str) -> Result<Grid<char>> {
+        parse(input)
+    }
+
+    fn part1(&self, image:&Grid<char>) -> Result<String> {
+        part1(image)
+    }
+
+    fn part2(&self, image:&Grid<char>) -> Result<String> {
+        part2(image)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
     }
 }
 
-fn parse(lines : Vec<&str>) -> Vec<Vec<char>> {
-    let galaxy = lines.iter()
-        .map(|line| line.chars().collect::<Vec<char>>())
-        .collect::<Vec<Vec<char>>>();
-    galaxy
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
 }
 
+pub fn parse(input:&str) -> Result<Grid<char>> {
+    Grid::parse_chars(input, ".#")
+}
 
-fn calc_total_dist(lines : Vec<&str>, empty_dist:usize) -> usize {
-    let galaxy_map = parse(lines);
-    let galaxies:Vec<(usize,usize)> = galaxy_map.iter().enumerate()
-        .map(|(y,row)| row.iter().enumerate()
-            .filter(|(_,c)| **c == '#')
-            .map(|(x, _)| (x ,y)).collect::<Vec<(usize,usize)>>())
-        .flatten()
-        .collect();
 
-    let mut empty_cols = vec![];
-    let mut empty_rows = vec![];
+fn calc_total_dist(galaxy_map:&Grid<char>, empty_dist:usize) -> usize {
+    let galaxies:Vec<Pos> = galaxy_map.iter()
+        .filter(|(_, c)| **c == '#')
+        .map(|(pos, _)| pos)
+        .collect();
 
-    // Check empty columns
-    for x in 0..galaxy_map.first().unwrap().len() {
-        let is_empty = (0..galaxy_map.len()).into_iter().map(|y| galaxy_map.get(y).unwrap().get(x).unwrap()).all(|c| *c == '.');
-        if is_empty {
-            empty_cols.push(x);
-        }
-    }
+    let empty_cols:Vec<usize> = (0..galaxy_map.width())
+        .filter(|x| galaxy_map.column(*x).all(|c| *c == '.'))
+        .collect();
 
-    // Check empty rows
-    for y in 0..galaxy_map.len() {
-        let is_empty = (0..galaxy_map.first().unwrap().len()).into_iter().map(|x| galaxy_map.get(y).unwrap().get(x).unwrap()).all(|c| *c == '.');
-        if is_empty {
-            empty_rows.push(y);
-        }
-    }
+    let empty_rows:Vec<usize> = galaxy_map.rows()
+        .enumerate()
+        .filter(|(_, row)| row.iter().all(|c| *c == '.'))
+        .map(|(y, _)| y)
+        .collect();
 
     let mut sum = 0;
     for galaxy_no in 0..galaxies.len() {
         let (x0,y0) = galaxies[galaxy_no];
-        for other_no in galaxy_no..galaxies.len() {
+        for (other_no, (x1, y1)) in galaxies.iter().copied().enumerate().skip(galaxy_no) {
             if galaxy_no != other_no {
-                let (x1,y1) = galaxies[other_no];
                 // Calc distance and count number of empty passes
                 let x_start = min(x0, x1);
                 let x_end = max(x0, x1);
@@ -70,24 +81,16 @@ fn calc_total_dist(lines : Vec<&str>, empty_dist:usize) -> usize {
 
     sum
 }
-fn part1(lines : Vec<&str>) -> String {
-    calc_total_dist(lines, 2).to_string()
+fn part1(image:&Grid<char>) -> Result<String> {
+    Ok(calc_total_dist(image, 2).to_string())
 }
 
-fn part2(lines : Vec<&str>) -> String {
-    calc_total_dist(lines, 1000000).to_string()
+fn part2(image:&Grid<char>) -> Result<String> {
+    Ok(calc_total_dist(image, 1000000).to_string())
 }
 
 
-#[cfg(test)]
-mod tests {
-    use std::assert_eq;
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-    use util::Part::{Part1, Part2};
-
-
-    const INPUT:&str = "...#......
+const EXAMPLE:&str = "...#......
 .......#..
 #.........
 ..........
@@ -98,25 +101,40 @@ mod tests {
 .......#..
 #...#.....";
 
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "374"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "82000210"},
+];
+
+
+#[cfg(test)]
+mod tests {
+    use std::assert_eq;
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
+
+
     #[test]
     fn test1() {
-        assert_eq!("374", solve(INPUT.to_string(), Part1));
+        assert_eq!("374", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_11.txt");
-        assert_eq!("9591768", solve(input.to_string(), Part1));
+        assert_answer(11, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("82000210", solve(INPUT.to_string(), Part2));
+        assert_eq!("82000210", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_11.txt");
-        assert_eq!("746962097860", solve(input.to_string(), Part2));
+        assert_answer(11, Part2, solve(input.to_string(), Part2));
     }
 }
@@ -1,33 +1,81 @@
 use std::collections::HashMap;
-use super::Part;
+use super::parallel;
+use super::{next_token, parse_token, Example, Part, Puzzle, Result, SolveError, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Vec<Record>;
+
+    fn day(&self) -> u8 {
+        12
+    }
+
+    fn title(&self) -> &'static str {
+        "Hot Springs"
+    }
+
+    fn parse(&self, input:&str) -> Result<Vec<Record>> {
+        parse(input)
+    }
+
+    fn part1(&self, records:&Vec<Record>) -> Result<String> {
+        part1(records)
+    }
+
+    fn part2(&self, records:&Vec<Record>) -> Result<String> {
+        part2(records)
     }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
+    }
+}
+
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
+
+
+/// One row of the condition records, springs as '.', '#' or '?' and the sizes of the damaged groups
+pub struct Record {
+    pub pattern:Vec<char>,
+    pub damaged:Vec<usize>,
 }
 
+pub fn parse(input:&str) -> Result<Vec<Record>> {
+    input.lines()
+        .enumerate()
+        .map(|(index, line)| parse_line(index, line))
+        .collect()
+}
 
-fn parse_line(line:&str, folds:usize) -> (Vec<char>, Vec<usize>) {
+fn parse_line(index:usize, line:&str) -> Result<Record> {
     let mut it = line.split(' ');
-    let pattern = it.next().unwrap().chars().collect::<Vec<char>>();
-    let damaged = it.next().unwrap().split(',').map(|s| s.parse::<usize>().unwrap()).collect::<Vec<usize>>();
+    let pattern_str = next_token(&mut it, index, line, "spring pattern")?;
+    if let Some(column) = pattern_str.chars().position(|c| c != '.' && c != '#' && c != '?') {
+        return Err(SolveError::malformed(index + 1, column + 1, "expected '.', '#' or '?'"));
+    }
+
+    let pattern = pattern_str.chars().collect::<Vec<char>>();
+    let damaged = next_token(&mut it, index, line, "damaged groups")?
+        .split(',')
+        .map(|s| parse_token::<usize>(index, line, s))
+        .collect::<Result<Vec<usize>>>()?;
+    Ok(Record{pattern, damaged})
+}
+
+fn unfold(record:&Record, folds:usize) -> (Vec<char>, Vec<usize>) {
     let mut unfolded_pattern = vec![];
     let mut unfolded_damaged = vec![];
 
     for i in 0..folds {
-        for j in 0..pattern.len() {
-            unfolded_pattern.push(pattern[j]);
-        }
+        unfolded_pattern.extend_from_slice(&record.pattern);
         if i < folds - 1 {
             unfolded_pattern.push('?');
         }
 
-        for j in 0..damaged.len() {
-            unfolded_damaged.push(damaged[j]);
-        }
+        unfolded_damaged.extend_from_slice(&record.damaged);
     }
 
     (unfolded_pattern, unfolded_damaged)
@@ -35,7 +83,7 @@ fn parse_line(line:&str, folds:usize) -> (Vec<char>, Vec<usize>) {
 
 fn permutations_for_pattern(pattern:&[char], damaged:&[usize], acc_sequence_len:usize, cache:&mut HashMap<(String, Vec<usize>, usize), usize>) -> usize {
     let pattern_str:String = pattern.iter().collect::<String>();
-    let dam_str = damaged.iter().copied().collect::<Vec<usize>>();
+    let dam_str = damaged.to_vec();
     let key = (pattern_str, dam_str, acc_sequence_len);
 
     if cache.contains_key(&key) {
@@ -116,60 +164,66 @@ fn permutations_for_pattern(pattern:&[char], damaged:&[usize], acc_sequence_len:
 }
 
 
-fn calc_total_permutations(lines : Vec<&str>, folds:usize) -> usize {
-    lines.iter()
-        .map(|line| parse_line(line, folds))
-        .map(|(patterns, damaged)|  permutations_for_pattern(patterns.as_slice(), damaged.as_slice(), 0, &mut HashMap::new()))
-        .sum::<usize>()
+fn calc_total_permutations(records:&[Record], folds:usize) -> usize {
+    parallel::map(records, |record| {
+        let (patterns, damaged) = unfold(record, folds);
+        permutations_for_pattern(patterns.as_slice(), damaged.as_slice(), 0, &mut HashMap::new())
+    }).into_iter().sum::<usize>()
 }
 
-fn part1(lines : Vec<&str>) -> String {
-    calc_total_permutations(lines, 1).to_string()
+fn part1(records:&[Record]) -> Result<String> {
+    Ok(calc_total_permutations(records, 1).to_string())
 }
 
 
-fn part2(lines : Vec<&str>) -> String {
-    calc_total_permutations(lines, 5).to_string()
+fn part2(records:&[Record]) -> Result<String> {
+    Ok(calc_total_permutations(records, 5).to_string())
 }
 
 
+const EXAMPLE:&str = "???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1";
+
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "21"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "525152"},
+];
+
+
 #[cfg(test)]
 mod tests {
     use std::assert_eq;
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     use util::Part::{Part1, Part2};
-
-
-    const INPUT:&str = "???.### 1,1,3
-.??..??...?##. 1,1,3
-?#?#?#?#?#?#?#? 1,3,1,6
-????.#...#... 4,1,1
-????.######..#####. 1,6,5
-?###???????? 3,2,1";
+    use util::answers::assert_answer;
 
 
     #[test]
     fn test1() {
-        assert_eq!("21", solve(INPUT.to_string(), Part1));
+        assert_eq!("21", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_12.txt");
-        assert_eq!("8193", solve(input.to_string(), Part1));
+        assert_answer(12, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("525152", solve(INPUT.to_string(), Part2));
+        assert_eq!("525152", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_12.txt");
-        assert_eq!("45322533163795", solve(input.to_string(), Part2));
+        assert_answer(12, Part2, solve(input.to_string(), Part2));
     }
 }
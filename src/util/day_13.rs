@@ -1,39 +1,63 @@
-use std::usize;
-use super::Part;
-
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.split("\n\n").collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+use super::grid::Grid;
+use super::{Example, Part, Puzzle, Result, SolveError, Solver};
+
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Vec<Grid<char>>;
+
+    fn day(&self) -> u8 {
+        13
+    }
+
+    fn title(&self) -> &'static str {
+        "Point of Incidence"
+    }
+
+    fn parse(&self, input:&str) -> Result<Vec<Grid<char>>> {
+        parse(input)
+    }
+
+    fn part1(&self, patterns:&Vec<Grid<char>>) -> Result<String> {
+        part1(patterns)
     }
-}
 
+    fn part2(&self, patterns:&Vec<Grid<char>>) -> Result<String> {
+        part2(patterns)
+    }
 
-fn parse_pattern(pattern:&str) -> Vec<Vec<char>> {
-    pattern.lines().map(|line| line.chars().collect::<Vec<char>>()).collect()
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
+    }
 }
 
-fn transpose(pattern:&Vec<Vec<char>>) -> Vec<Vec<char>> {
-    let mut transposed = vec![];
-    for x in 0..pattern.first().unwrap().len() {
-        let mut row = vec![];
-        for y in 0..pattern.len() {
-            row.push(*pattern.get(y).unwrap().get(x).unwrap());
-        }
-        transposed.push(row);
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
+
+
+pub fn parse(input:&str) -> Result<Vec<Grid<char>>> {
+    let mut patterns = vec![];
+    let mut index = 0;
+    for pattern in input.split("\n\n") {
+        patterns.push(parse_pattern(index, pattern)?);
+        index += pattern.lines().count() + 1;
     }
-    transposed
+    Ok(patterns)
+}
+
+fn parse_pattern(first_index:usize, pattern:&str) -> Result<Grid<char>> {
+    Grid::parse_at(first_index, pattern, |ch| Some(ch).filter(|ch| *ch == '.' || *ch == '#'), "expected '.' or '#'")
 }
 
-fn find_pattern(pattern:&Vec<Vec<char>>) -> Vec<usize> {
+fn find_pattern(pattern:&Grid<char>) -> Vec<usize> {
     let mut result = vec![];
-    for y in 1..pattern.len() {
+    for y in 1..pattern.height() {
         let mut y1 = y;
         let mut y2 = y;
         let mut reflection = true;
-        while reflection && y1 > 0 && y2 < pattern.len() {
-            reflection = reflection && pattern.get(y1-1).unwrap().eq(pattern.get(y2).unwrap());
+        while reflection && y1 > 0 && y2 < pattern.height() {
+            reflection = reflection && pattern.row(y1-1) == pattern.row(y2);
             y1 -= 1;
             y2 += 1;
         }
@@ -43,39 +67,45 @@ fn find_pattern(pattern:&Vec<Vec<char>>) -> Vec<usize> {
         }
     }
 
-    return result;
+    result
 }
 
-fn get_score(matrix: &Vec<Vec<char>>) -> Vec<usize> {
+fn get_score(matrix: &Grid<char>) -> Vec<usize> {
     let mut result = vec![];
-    result.append(&mut find_pattern(&matrix).into_iter().filter(|row| *row > 0).map(|row| row * 100).collect());
-    result.append(&mut find_pattern(&transpose(&matrix)).into_iter().filter(|col| *col > 0).collect());
+    result.append(&mut find_pattern(matrix).into_iter().filter(|row| *row > 0).map(|row| row * 100).collect());
+    result.append(&mut find_pattern(&matrix.transpose()).into_iter().filter(|col| *col > 0).collect());
     result
 }
 
-fn part1(input : Vec<&str>) -> String {
-    input.into_iter()
-        .map(|pattern| parse_pattern(pattern))
-        .map(|matrix|  get_score(&matrix)[0])
-        .sum::<usize>()
-        .to_string()
+fn reflection_score(matrix: &Grid<char>) -> Result<usize> {
+    get_score(matrix).first()
+        .copied()
+        .ok_or_else(|| SolveError::unsolvable("pattern has no line of reflection"))
 }
 
+fn part1(patterns:&[Grid<char>]) -> Result<String> {
+    Ok(patterns.iter()
+        .map(reflection_score)
+        .sum::<Result<usize>>()?
+        .to_string())
+}
 
-fn part2(input : Vec<&str>) -> String {
+
+fn part2(patterns:&[Grid<char>]) -> Result<String> {
     let mut sum = 0;
-    for pattern in input.iter() {
-        let mut matrix = parse_pattern(pattern);
-        let old_score = get_score(&matrix)[0];
-        'outer_loop: for y in 0..matrix.len() {
-            for x in 0..matrix.first().unwrap().len() {
-                let old_char = matrix[y][x];
+    for matrix in patterns {
+        let mut matrix = matrix.clone();
+        let old_score = reflection_score(&matrix)?;
+        let mut found = false;
+        'outer_loop: for y in 0..matrix.height() {
+            for x in 0..matrix.width() {
+                let old_char = matrix[(x, y)];
                 let next_char = match old_char {
                     '.' => '#',
                     _ => '.',
                 };
 
-                matrix[y][x] = next_char;
+                matrix[(x, y)] = next_char;
 
                 // Check for score after altering matrix
                 let scores = get_score(&matrix).iter()
@@ -84,29 +114,27 @@ fn part2(input : Vec<&str>) -> String {
                     .collect::<Vec<usize>>();
 
                 // Restore matrix
-                matrix[y][x] = old_char;
+                matrix[(x, y)] = old_char;
 
                 if scores.len() == 1 {
                     sum += scores[0];
+                    found = true;
                     break 'outer_loop;
                 }
             }
         }
+
+        if !found {
+            return Err(SolveError::unsolvable("pattern has no smudge that gives a new line of reflection"));
+        }
     }
 
-    sum.to_string()
+    Ok(sum.to_string())
 
 }
 
 
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-    use util::Part::{Part1, Part2};
-
-
-    const INPUT:&str = "#.##..##.
+const EXAMPLE:&str = "#.##..##.
 ..#.##.#.
 ##......#
 ##......#
@@ -122,26 +150,40 @@ mod tests {
 ..##..###
 #....#..#";
 
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "405"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "400"},
+];
+
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
+
+
     #[test]
     fn test1() {
-        assert_eq!("405", solve(INPUT.to_string(), Part1));
+        assert_eq!("405", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_13.txt");
-        assert_eq!("28651", solve(input.to_string(), Part1));
+        assert_answer(13, Part1, solve(input.to_string(), Part1));
     }
 
 
     #[test]
     fn test2() {
-        assert_eq!("400", solve(INPUT.to_string(), Part2));
+        assert_eq!("400", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_13.txt");
-        assert_eq!("25450", solve(input.to_string(), Part2));
+        assert_answer(13, Part2, solve(input.to_string(), Part2));
     }
 }
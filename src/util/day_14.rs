@@ -1,94 +1,88 @@
-use super::Part;
+use super::grid::{Direction, Grid, Pos};
+use super::{Example, Part, Puzzle, Result, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Grid<char>;
+
+    fn day(&self) -> u8 {
+        14
     }
-}
 
+    fn title(&self) -> &'static str {
+        "Parabolic Reflector Dish"
+    }
 
-fn parse_platform(lines : Vec<&str>) -> Vec<Vec<char>> {
-    lines.iter().map(|line| line.chars().collect::<Vec<char>>()).collect()
-}
+    fn parse(&self, input:&str) -> Result<Grid<char>> {
+        parse(input)
+    }
 
-fn move_rocks_horizontal(platform:&mut Vec<Vec<char>>, left:bool) {
-    for y in 0..platform.first().unwrap().len() {
-        loop {
-            let mut swap = false;
-            for x in 1..platform.len() {
-                if left && platform[y][x] == 'O' && platform[y][x - 1] == '.' {
-                    // swap
-                    platform[y][x] = '.';
-                    platform[y][x - 1] = 'O';
-                    swap = true;
-                } else if !left && platform[y][x] == '.' && platform[y][x - 1] == 'O' {
-                    // swap
-                    platform[y][x] = 'O';
-                    platform[y][x - 1] = '.';
-                    swap = true;
-                }
-            }
+    fn part1(&self, platform:&Grid<char>) -> Result<String> {
+        part1(platform)
+    }
 
-            if swap == false {
-                break;
-            }
-        }
+    fn part2(&self, platform:&Grid<char>) -> Result<String> {
+        part2(platform)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
     }
 }
 
-fn move_rocks_vertical(platform:&mut Vec<Vec<char>>, up:bool) {
-    for x in 0..platform.first().unwrap().len() {
-        loop {
-            let mut swap = false;
-            for y in 1..platform.len() {
-                if up && platform[y][x] == 'O' && platform[y - 1][x] == '.' {
-                    // swap
-                    platform[y][x] = '.';
-                    platform[y - 1][x] = 'O';
-                    swap = true;
-                } else if !up && platform[y][x] == '.' && platform[y - 1][x] == 'O' {
-                    // swap
-                    platform[y][x] = 'O';
-                    platform[y - 1][x] = '.';
-                    swap = true;
-                }
-            }
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
 
-            if swap == false {
-                break;
-            }
+
+pub fn parse(input:&str) -> Result<Grid<char>> {
+    Grid::parse_chars(input, ".#O")
+}
+
+// Rolls every rounded rock as far as it goes, starting with the rocks closest to the edge they roll towards
+fn roll(platform:&mut Grid<char>, direction:Direction) {
+    let mut positions = platform.positions().collect::<Vec<Pos>>();
+    if direction == Direction::Down || direction == Direction::Right {
+        positions.reverse();
+    }
+
+    for pos in positions {
+        if platform[pos] != 'O' {
+            continue;
+        }
+
+        let mut current = pos;
+        while let Some(next) = platform.step(current, direction).filter(|next| platform[*next] == '.') {
+            current = next;
         }
+        platform[pos] = '.';
+        platform[current] = 'O';
     }
 }
 
-fn calc_weight(platform:&Vec<Vec<char>>) -> usize {
+fn calc_weight(platform:&Grid<char>) -> usize {
     platform.iter()
-        .enumerate()
-        .map(|(y,row)| row.iter()
-            .filter(|item| **item == 'O')
-            .map(|_| platform.len() - y)
-            .sum::<usize>())
+        .filter(|(_, item)| **item == 'O')
+        .map(|((_, y), _)| platform.height() - y)
         .sum::<usize>()
 }
 
 
 
-fn part1(lines : Vec<&str>) -> String {
-    let mut platform = parse_platform(lines);
-    move_rocks_vertical(&mut platform, true);
-    calc_weight(&platform).to_string()
+fn part1(platform:&Grid<char>) -> Result<String> {
+    let mut platform = platform.clone();
+    roll(&mut platform, Direction::Up);
+    Ok(calc_weight(&platform).to_string())
 }
 
-fn tilt(platform:&mut Vec<Vec<char>>) {
-    move_rocks_vertical(platform, true);
-    move_rocks_horizontal(platform, true);
-    move_rocks_vertical(platform, false);
-    move_rocks_horizontal(platform, false);
+fn tilt(platform:&mut Grid<char>) {
+    for direction in [Direction::Up, Direction::Left, Direction::Down, Direction::Right].iter() {
+        roll(platform, *direction);
+    }
 }
 
-fn check_cycle(numbers:&Vec<usize>) -> Option<usize> {
+fn check_cycle(numbers:&[usize]) -> Option<usize> {
     for cycle_len in 4..numbers.len() / 2 {
         let mut matched = true;
         for n in 0..cycle_len {
@@ -104,36 +98,25 @@ fn check_cycle(numbers:&Vec<usize>) -> Option<usize> {
 
     None
 }
-fn part2(lines : Vec<&str>) -> String {
-    let mut platform = parse_platform(lines);
+fn part2(platform:&Grid<char>) -> Result<String> {
+    let mut platform = platform.clone();
     let mut weights = vec![];
     loop {
         tilt(&mut platform);
         weights.push(calc_weight(&platform));
-        match check_cycle(&weights) {
-            Some(cycle_len) => {
-                let rest = (1000000000 - weights.len()) % cycle_len;
-                for _ in 0..rest {
-                    tilt(&mut platform);
-                }
-
-                return calc_weight(&platform).to_string()
-            },
-            _ => {},
+        if let Some(cycle_len) = check_cycle(&weights) {
+            let rest = (1000000000 - weights.len()) % cycle_len;
+            for _ in 0..rest {
+                tilt(&mut platform);
+            }
+
+            return Ok(calc_weight(&platform).to_string())
         }
     }
 }
 
 
-#[cfg(test)]
-mod tests {
-    use std::assert_eq;
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-    use util::Part::{Part1, Part2};
-
-
-    const INPUT:&str = "O....#....
+const EXAMPLE:&str = "O....#....
 O.OO#....#
 .....##...
 OO.#O....O
@@ -144,25 +127,40 @@ O.#..O.#.#
 #....###..
 #OO..#....";
 
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "136"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "64"},
+];
+
+
+#[cfg(test)]
+mod tests {
+    use std::assert_eq;
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
+
+
     #[test]
     fn test1() {
-        assert_eq!("136", solve(INPUT.to_string(), Part1));
+        assert_eq!("136", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_14.txt");
-        assert_eq!("109098", solve(input.to_string(), Part1));
+        assert_answer(14, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("64", solve(INPUT.to_string(), Part2));
+        assert_eq!("64", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_14.txt");
-        assert_eq!("100064", solve(input.to_string(), Part2));
+        assert_answer(14, Part2, solve(input.to_string(), Part2));
     }
 }
@@ -1,35 +1,76 @@
-use util::day_15::Operation::{ADD, REMOVE};
-use super::Part;
-
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.split(',').into_iter().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+use util::day_15::Operation::{Add, Remove};
+use super::{next_token, parse_token, Example, Part, Puzzle, Result, SolveError, Solver};
+
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Vec<Step>;
+
+    fn day(&self) -> u8 {
+        15
+    }
+
+    fn title(&self) -> &'static str {
+        "Lens Library"
+    }
+
+    fn parse(&self, input:&str) -> Result<Vec<Step>> {
+        parse(input)
+    }
+
+    fn part1(&self, steps:&Vec<Step>) -> Result<String> {
+        part1(steps)
+    }
+
+    fn part2(&self, steps:&Vec<Step>) -> Result<String> {
+        part2(steps)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
     }
 }
 
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
+
+/// One comma separated step of the initialization sequence
+pub struct Step {
+    pub text:String,
+    pub operation:Operation,
+}
+
+pub fn parse(input:&str) -> Result<Vec<Step>> {
+    let sequence = input.trim_end_matches(['\r', '\n']);
+    sequence.split(',')
+        .map(|text| Ok(Step{text: text.to_string(), operation: Operation::new(sequence, text)?}))
+        .collect()
+}
+
 
-enum Operation {
-    ADD(String, usize),
-    REMOVE(String),
+pub enum Operation {
+    Add(String, usize),
+    Remove(String),
 }
 
 impl Operation {
-    fn new(op_str:&str) -> Operation {
-        let mut it = op_str.split(|c| c == '=' || c == '-');
-        let label = it.next().unwrap().to_string();
-        if op_str.contains("-") {
-            REMOVE(label)
+    fn new(sequence:&str, op_str:&str) -> Result<Operation> {
+        let mut it = op_str.split(['=', '-']);
+        let label = next_token(&mut it, 0, sequence, "label")?.to_string();
+        if op_str.ends_with('-') {
+            Ok(Remove(label))
+        } else if op_str.contains('=') {
+            Ok(Add(label, parse_token(0, sequence, next_token(&mut it, 0, sequence, "focal length")?)?))
         } else {
-            ADD(label, it.next().unwrap().parse::<usize>().unwrap())
+            Err(SolveError::at_token(0, sequence, op_str, format!("unexpected step '{}'", op_str)))
         }
     }
 
     fn get_label(&self) -> &String {
         match self {
-            ADD(label, _) => label,
-            REMOVE(label) => label,
+            Add(label, _) => label,
+            Remove(label) => label,
         }
     }
 
@@ -43,75 +84,80 @@ impl Operation {
     }
 }
 
-fn part1(ops: Vec<&str>) -> String {
-    ops.iter()
-        .map(|line|Operation::hash_chars(line))
+fn part1(steps:&[Step]) -> Result<String> {
+    Ok(steps.iter()
+        .map(|step| Operation::hash_chars(&step.text))
         .sum::<usize>()
-        .to_string()
+        .to_string())
 }
 
-fn part2(ops : Vec<&str>) -> String {
-    let mut boxes:Vec<Vec<(String, usize)>> = (0..256).into_iter().map(|_| Vec::new()).collect();
-    ops.iter().map(|line| Operation::new(line))
-        .for_each(|operation| {
+fn part2(steps:&[Step]) -> Result<String> {
+    let mut boxes:Vec<Vec<(String, usize)>> = (0..256).map(|_| Vec::new()).collect();
+    steps.iter()
+        .for_each(|Step{operation, ..}| {
             let curr_box = boxes.get_mut(operation.hash_label()).unwrap();
             let index_opt =  curr_box.iter().enumerate()
                 .find(|(_, (l, _))| l.eq(operation.get_label()))
                 .map(|(index, _)| index);
 
             match operation {
-                ADD(label, focal_len) => {
+                Add(label, focal_len) => {
                     match index_opt {
-                        None => curr_box.push((label, focal_len)),
-                        Some(index) =>  curr_box[index].1 = focal_len,
+                        None => curr_box.push((label.clone(), *focal_len)),
+                        Some(index) =>  curr_box[index].1 = *focal_len,
                     };
                 }
-                REMOVE(_) => {
-                    match index_opt {
-                        Some(index) => {curr_box.remove(index);},
-                        None => {},
-                    };
+                Remove(_) => {
+                    if let Some(index) = index_opt {curr_box.remove(index);};
                 }
             }
         });
 
     // Sum focal power
-    boxes.into_iter().enumerate()
+    Ok(boxes.into_iter().enumerate()
         .map(|(box_no, curr_box)| curr_box.into_iter().enumerate()
             .map(|(box_pos, (_, focal_len))| (box_no + 1) * (box_pos + 1) * focal_len)
             .sum::<usize>())
-        .sum::<usize>().to_string()
+        .sum::<usize>().to_string())
 }
 
 
+const EXAMPLE:&str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "1320"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "145"},
+];
+
+
 #[cfg(test)]
 mod tests {
     use std::assert_eq;
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
 
-    const INPUT:&str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
 
     #[test]
     fn test1() {
-        assert_eq!("1320", solve(INPUT.to_string(), Part1));
+        assert_eq!("1320", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_15.txt");
-        assert_eq!("516657", solve(input.to_string(), Part1));
+        assert_answer(15, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("145", solve(INPUT.to_string(), Part2));
+        assert_eq!("145", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_15.txt");
-        assert_eq!("210906", solve(input.to_string(), Part2));
+        assert_answer(15, Part2, solve(input.to_string(), Part2));
     }
 }
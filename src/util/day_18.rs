@@ -1,72 +1,105 @@
-use super::Part;
+use super::grid::Direction;
+use super::{next_token, parse_token, Example, Part, Puzzle, Result, SolveError, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Vec<Instruction>;
+
+    fn day(&self) -> u8 {
+        18
+    }
+
+    fn title(&self) -> &'static str {
+        "Lavaduct Lagoon"
+    }
+
+    fn parse(&self, input:&str) -> Result<Vec<Instruction>> {
+        parse(input)
+    }
+
+    fn part1(&self, plan:&Vec<Instruction>) -> Result<String> {
+        part1(plan)
+    }
+
+    fn part2(&self, plan:&Vec<Instruction>) -> Result<String> {
+        part2(plan)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
     }
 }
 
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
 }
 
-impl Direction {
-    fn new(s:&str) -> Direction {
-        match s.chars().next().unwrap() {
-            'U' => Direction::Up,
-            'D' => Direction::Down,
-            'L' => Direction::Left,
-            'R' => Direction::Right,
-            '0' => Direction::Right,
-            '1' => Direction::Down,
-            '2' => Direction::Left,
-            '3' => Direction::Up,
-            _ => panic!("..."),
-        }
+// Direction written as U, D, L or R, or hidden in the color as a digit 0 to 3
+fn direction(s:&str) -> Option<Direction> {
+    match s.chars().next()? {
+        'U' => Some(Direction::Up),
+        'D' => Some(Direction::Down),
+        'L' => Some(Direction::Left),
+        'R' => Some(Direction::Right),
+        '0' => Some(Direction::Right),
+        '1' => Some(Direction::Down),
+        '2' => Some(Direction::Left),
+        '3' => Some(Direction::Up),
+        _ => None,
     }
 }
 
-fn parse_line(line:&str, part2: bool) -> (Direction, i64) {
+/// One line of the dig plan, read both as written and with the steps hidden in the color
+pub struct Instruction {
+    pub dig:(Direction, i64),
+    pub color:(Direction, i64),
+}
+
+pub fn parse(input:&str) -> Result<Vec<Instruction>> {
+    input.lines()
+        .enumerate()
+        .map(|(index, line)| parse_line(index, line))
+        .collect()
+}
+
+fn parse_line(index:usize, line:&str) -> Result<Instruction> {
     let mut it = line.split(' ');
-    if part2 {
-        let hex_str = &it.last().unwrap()[2..8];
-        let ch = &hex_str[5..];
-        let dir = Direction::new(ch);
-        let number = u64::from_str_radix(&hex_str[0..5], 16).unwrap() as i64;
-        (dir, number)
-    } else {
-        let dir = Direction::new(it.next().unwrap());
-        let len = it.next().unwrap().parse::<i64>().unwrap();
-        (dir, len)
-    }
+    let dir_str = next_token(&mut it, index, line, "direction")?;
+    let len_str = next_token(&mut it, index, line, "length")?;
+    let dir = direction(dir_str)
+        .filter(|_| dir_str.len() == 1)
+        .ok_or_else(|| SolveError::at_token(index, line, dir_str, "expected direction U, D, L or R"))?;
+    let len = parse_token::<i64>(index, line, len_str)?;
+
+    let color = next_token(&mut it, index, line, "color")?;
+    let hex_str = color.strip_prefix("(#")
+        .and_then(|s| s.strip_suffix(')'))
+        .filter(|s| s.len() == 6 && s.is_ascii())
+        .ok_or_else(|| SolveError::at_token(index, line, color, "expected color '(#rrggbb)'"))?;
+    let color_dir = direction(&hex_str[5..])
+        .ok_or_else(|| SolveError::at_token(index, line, &hex_str[5..], "expected direction digit 0-3"))?;
+    let number = u64::from_str_radix(&hex_str[0..5], 16)
+        .map_err(|_| SolveError::at_token(index, line, hex_str, "expected hexadecimal length"))? as i64;
+
+    Ok(Instruction{dig: (dir, len), color: (color_dir, number)})
 }
 
 
-fn build_map(lines : Vec<&str>, part2:bool) -> Vec<(i64,i64)> {
+fn build_map(steps:impl Iterator<Item=(Direction, i64)>) -> Vec<(i64,i64)> {
     let mut points = vec![];
     points.push((0,0));
-    lines.iter().map(|line| parse_line(line, part2))
-        .for_each(|(dir, len)| {
+    steps.for_each(|(dir, len)| {
             let (x,y) = *points.last().unwrap();
-            let next_pos = match dir {
-                Direction::Up => (x, y - len),
-                Direction::Down => (x, y + len),
-                Direction::Left => (x - len, y),
-                Direction::Right => (x + len, y),
-            };
-            points.push(next_pos);
+            let (dx, dy) = dir.delta();
+            points.push((x + dx as i64 * len, y + dy as i64 * len));
         });
     points
 }
 
 
 
-fn shoelace_formula(points:&Vec<(i64,i64)>) -> i64 {
+fn shoelace_formula(points:&[(i64,i64)]) -> i64 {
     let mut area = 0;
     let mut length = 0;
 
@@ -81,25 +114,18 @@ fn shoelace_formula(points:&Vec<(i64,i64)>) -> i64 {
 }
 
 
-fn part1(lines : Vec<&str>) -> String {
-    let points = build_map(lines, false);
-    shoelace_formula(&points).to_string()
+fn part1(plan:&[Instruction]) -> Result<String> {
+    let points = build_map(plan.iter().map(|instruction| instruction.dig));
+    Ok(shoelace_formula(&points).to_string())
 }
 
-fn part2(lines : Vec<&str>) -> String {
-    let points = build_map(lines, true);
-    shoelace_formula(&points).to_string()
+fn part2(plan:&[Instruction]) -> Result<String> {
+    let points = build_map(plan.iter().map(|instruction| instruction.color));
+    Ok(shoelace_formula(&points).to_string())
 }
 
 
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-    use util::Part::{Part1, Part2};
-
-
-    const INPUT:&str = "R 6 (#70c710)
+const EXAMPLE:&str = "R 6 (#70c710)
 D 5 (#0dc571)
 L 2 (#5713f0)
 D 2 (#d2c081)
@@ -114,27 +140,39 @@ U 3 (#a77fa3)
 L 2 (#015232)
 U 2 (#7a21e3)";
 
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "62"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "952408144115"},
+];
+
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
 
 
     #[test]
     fn test1() {
-        assert_eq!("62", solve(INPUT.to_string(), Part1));
+        assert_eq!("62", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_18.txt");
-        assert_eq!("38188", solve(input.to_string(), Part1));
+        assert_answer(18, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("952408144115", solve(INPUT.to_string(), Part2));
+        assert_eq!("952408144115", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_18.txt");
-        assert_eq!("93325849869340", solve(input.to_string(), Part2));
+        assert_answer(18, Part2, solve(input.to_string(), Part2));
     }
 }
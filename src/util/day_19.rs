@@ -1,34 +1,95 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Debug;
+use std::rc::Rc;
 use util::day_19::RuleResult::{Accepted, Rejected};
-use super::Part;
+use super::interval::Interval;
+use super::{next_token, parse_token, Example, Part, Puzzle, Result, SolveError, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
-    let mut items:Vec<Vec<&str>> = input.split("\n\n")
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = System;
+
+    fn day(&self) -> u8 {
+        19
+    }
+
+    fn title(&self) -> &'static str {
+        "Aplenty"
+    }
+
+    fn parse(&self, input:&str) -> Result<System> {
+        parse(input)
+    }
+
+    fn part1(&self, system:&System) -> Result<String> {
+        part1(system)
+    }
+
+    fn part2(&self, system:&System) -> Result<String> {
+        part2(system)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
+    }
+}
+
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
+
+/// The workflows and the machine parts to sort through them
+pub struct System {
+    pub rules:Vec<Rule>,
+    pub parts:Vec<MachinePart>,
+}
+
+pub fn parse(input:&str) -> Result<System> {
+    let items:Vec<Vec<&str>> = input.split("\n\n")
         .map(|lines| lines.lines().collect()).collect();
-    match part {
-        Part::Part1 => part1(items.remove(0), items.remove(0)),
-        Part::Part2 => part2(items.remove(0), items.remove(0))
+    if items.len() != 2 {
+        return Err(SolveError::malformed(1, 1, "expected workflows and ratings separated by an empty line"));
     }
+
+    let rules = items[0].iter()
+        .enumerate()
+        .map(|(index, line)| Rule::new(index, line))
+        .collect::<Result<Vec<Rule>>>()?;
+    let first_index = items[0].len() + 1;
+    let parts = items[1].iter()
+        .enumerate()
+        .map(|(index, line)| MachinePart::new(first_index + index, line))
+        .collect::<Result<Vec<MachinePart>>>()?;
+    Ok(System{rules, parts})
 }
 
-#[derive(Debug)]
-struct MachinePart {
-    x:u32,
-    m:u32,
-    a:u32,
-    s:u32,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MachinePart {
+    pub x:u32,
+    pub m:u32,
+    pub a:u32,
+    pub s:u32,
 }
 
 impl MachinePart {
-    fn get_field_value(&self, ch:char) -> u32 {
-        match ch {
-            'x' => self.x,
-            'm' => self.m,
-            'a' => self.a,
-            's' => self.s,
-            _ => panic!(".."),
+    fn new(index:usize, line:&str) -> Result<MachinePart> {
+        let mut ratings = [0;4];
+        let fields = line.trim_start_matches('{').trim_end_matches('}').split(',').collect::<Vec<&str>>();
+        if fields.len() != ratings.len() {
+            return Err(SolveError::malformed(index + 1, 1, "expected '{x=..,m=..,a=..,s=..}'"));
+        }
+        for ((field, rating), name) in fields.into_iter().zip(ratings.iter_mut()).zip(["x=", "m=", "a=", "s="]) {
+            let value = field.strip_prefix(name)
+                .ok_or_else(|| SolveError::at_token(index, line, field, format!("expected '{}'", name)))?;
+            *rating = parse_token::<u32>(index, line, value)?;
         }
+        Ok(MachinePart{x:ratings[0], m:ratings[1], a:ratings[2], s:ratings[3]})
+    }
+
+    /// The x, m, a and s ratings, in that order
+    pub fn ratings(&self) -> [u32;4] {
+        [self.x, self.m, self.a, self.s]
     }
 
     fn sum(&self) -> u32 {
@@ -37,9 +98,14 @@ impl MachinePart {
 }
 
 
+/// Range of each rating, in the order x, m, a, s, so together a box of parts
+pub type Ratings = [Interval;4];
+
+/// Every rating from 1 to 4000, the parts the puzzle asks about
+pub const ALL_RATINGS:Ratings = [Interval::new(1, 4001);4];
 
 #[derive(Debug)]
-struct  Condition {
+pub struct Condition {
     field_name:char,
     comparator:char,
     value:u32,
@@ -48,94 +114,53 @@ struct  Condition {
 
 impl Condition {
 
-    fn new(s:&str) -> Condition {
+    fn new(index:usize, line:&str, s:&str) -> Result<Condition> {
         let mut it = s.chars();
-        let field_name = it.next().unwrap();
-        let comparator = it.next().unwrap();
-        let value = *&s[2..].parse::<u32>().unwrap();
-        Condition{ field_name, comparator, value}
-    }
-
-    fn get_field_name(&self) -> char {
-        self.field_name
-    }
-    fn match_part(&self, machine_part:&MachinePart) -> bool {
-        let field_value = machine_part.get_field_value(self.get_field_name());
-        self.comparator == '>' && self.value < field_value || self.comparator == '<' && self.value > field_value
-    }
-
-    fn match_intervals(&self, intervals:Vec<(char, u32,u32)>) -> (Vec<(char, u32,u32)>, Vec<(char, u32,u32)>) {
-        let mut matched_intervals = vec![];
-        let mut unmatched_intervals = vec![];
-        for (ch, start, end) in intervals {
-            if ch == self.field_name {
-                let (matched, unmatched) = self.match_interval((start, end));
-                matched_intervals.push((ch, matched.0, matched.1));
-                unmatched_intervals.push((ch, unmatched.0, unmatched.1));
-            } else {
-                matched_intervals.push((ch, start, end));
-                unmatched_intervals.push((ch, start, end));
-            }
-        }
-
-        (matched_intervals, unmatched_intervals)
+        let field_name = it.next().filter(|c| "xmas".contains(*c))
+            .ok_or_else(|| SolveError::at_token(index, line, s, "expected rating x, m, a or s"))?;
+        let comparator = it.next().filter(|c| *c == '<' || *c == '>')
+            .ok_or_else(|| SolveError::at_token(index, line, &s[1..], "expected comparator '<' or '>'"))?;
+        let value = parse_token::<u32>(index, line, &s[2..])?;
+        Ok(Condition{ field_name, comparator, value})
     }
 
-    fn match_interval(&self, (start, end):(u32,u32)) -> ((u32,u32),(u32,u32)) {
-        if self.comparator == '>' {
-            ((self.value+1, end),(start, self.value))
-        } else {
-            ((start, self.value-1), (self.value, end))
-        }
+    // Index of the rating the condition is on, in `Ratings` order
+    fn rating(&self) -> usize {
+        "xmas".find(self.field_name).unwrap()
     }
 }
 
 #[derive(Debug)]
-struct Rule {
+pub struct Rule {
     name:String,
     conditions:Vec<(Condition, RuleResult)>,
     default_rule:RuleResult,
 }
 
 impl Rule {
-    fn new(line:&str) -> Rule {
-        let mut fields = line.split(|c| c == '{' || c == '}' || c == ',').filter(|s| !s.is_empty()).collect::<VecDeque<&str>>();
-        let name = fields.pop_front().unwrap().to_string();
-        let default_rule = RuleResult::new(fields.pop_back().unwrap());
+    fn new(index:usize, line:&str) -> Result<Rule> {
+        if !line.ends_with('}') {
+            return Err(SolveError::missing(index, line, "'}'"));
+        }
+        let mut fields = line.split(['{', '}', ',']).filter(|s| !s.is_empty()).collect::<VecDeque<&str>>();
+        let name = fields.pop_front()
+            .ok_or_else(|| SolveError::missing(index, line, "workflow name"))?
+            .to_string();
+        let default_rule = RuleResult::new(fields.pop_back()
+            .ok_or_else(|| SolveError::missing(index, line, "default rule"))?);
         let mut conditions = vec![];
         for field in fields {
-            for rule_str in field.split(',') {
-                let mut it = rule_str.split(':');
-                conditions.push((Condition::new(it.next().unwrap()), RuleResult::new(it.next().unwrap())));
-            }
+            let mut it = field.split(':');
+            let condition = Condition::new(index, line, next_token(&mut it, index, line, "condition")?)?;
+            let target = next_token(&mut it, index, line, "rule target")?;
+            conditions.push((condition, RuleResult::new(target)));
         }
-        Rule{name, conditions, default_rule}
-    }
-
-    fn match_part(&self, machine_part:&MachinePart) -> RuleResult {
-        self.conditions.iter()
-            .find(|(condition, _)| condition.match_part(machine_part))
-            .map_or(self.default_rule.clone(), |(_, res)| res.clone())
-    }
-
-
-    fn match_interval(&self, start_intervals:Vec<(char,u32,u32)>) -> Vec<(Vec<(char,u32,u32)>, RuleResult)> {
-        let mut output = vec![];
-        let mut stack = VecDeque::new();
-        stack.push_back(start_intervals);
-        for (cond, rule_result) in self.conditions.iter() {
-            let intervals = stack.pop_front().unwrap();
-            let (matched, unmatched) = cond.match_intervals(intervals);
-            output.push((matched, rule_result.clone()));
-            stack.push_front(unmatched);
-        }
-        output.push((stack.pop_front().unwrap(), self.default_rule.clone()));
-        output
+        Ok(Rule{name, conditions, default_rule})
     }
 }
 
 #[derive(Debug, Clone)]
-enum RuleResult {
+pub enum RuleResult {
     Accepted,
     Rejected,
     Rule(String),
@@ -154,95 +179,123 @@ impl RuleResult {
 }
 
 
-impl MachinePart {
-    fn new(line:&str) -> MachinePart {
-        let numbers = line.split(|ch|  ch == '{' || ch == '}' || ch == '=' || ch == ',')
-            .filter(|s| !s.is_empty())
-            .filter(|s| s.starts_with(|c:char| c.is_digit(10)))
-            .map(|s| s.parse::<u32>().unwrap())
-            .collect::<Vec<u32>>();
-        MachinePart{x:numbers[0], m:numbers[1], a:numbers[2], s:numbers[3]}
-    }
+/// The workflows compiled into a single tree of decisions on one rating at a time, starting from
+/// workflow `in`. A workflow sent to from several places is compiled once and its subtree shared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecisionTree {
+    Accept,
+    Reject,
+    /// Parts whose `rating` (in `Ratings` order) is below `value` go to `below`, the others to `above`
+    Split{rating:usize, value:u32, below:Rc<DecisionTree>, above:Rc<DecisionTree>},
 }
 
+impl DecisionTree {
+    pub fn compile(rules:&[Rule]) -> Result<DecisionTree> {
+        let workflows = rules.iter().map(|rule| (rule.name.as_str(), rule)).collect::<HashMap<&str, &Rule>>();
+        let tree = DecisionTree::compile_result(&workflows, &RuleResult::Rule("in".to_string()), &mut vec![], &mut HashMap::new())?;
+        Ok(tree.as_ref().clone())
+    }
 
+    // Tree for whatever `result` sends a part to, `path` holds the workflows that led here so a
+    // workflow sending parts back to one of them is caught, `compiled` the trees of the workflows
+    // done so far
+    fn compile_result<'a>(workflows:&HashMap<&str, &'a Rule>, result:&RuleResult, path:&mut Vec<&'a str>,
+                          compiled:&mut HashMap<&'a str, Rc<DecisionTree>>) -> Result<Rc<DecisionTree>> {
+        let name = match result {
+            Accepted => return Ok(Rc::new(DecisionTree::Accept)),
+            Rejected => return Ok(Rc::new(DecisionTree::Reject)),
+            RuleResult::Rule(name) => name,
+        };
+        let rule = *workflows.get(name.as_str())
+            .ok_or_else(|| SolveError::unsolvable(format!("unknown workflow {}", name)))?;
+        // A workflow compiled before can't lead back to the path, or it would have been caught then
+        if let Some(tree) = compiled.get(rule.name.as_str()) {
+            return Ok(tree.clone());
+        }
+        if path.contains(&rule.name.as_str()) {
+            return Err(SolveError::unsolvable("workflows send a part around in a loop"));
+        }
 
-fn match_rule(part:&MachinePart, rules:&Vec<Rule>) -> bool {
-    let mut stack = VecDeque::new();
-    stack.push_back(String::from("in"));
-    loop {
-        let rule_name = stack.pop_front().unwrap();
-        let next_rule = rules.iter().find(|rule| rule.name.as_str().eq(rule_name.as_str())).unwrap();
-        let res = next_rule.match_part(part);
-        match res {
-            Accepted => {
-                return true;
-            },
-            Rejected => {
-                return false;
-            }
-            RuleResult::Rule(rule_name) => {
-                stack.push_back(rule_name);
-            },
+        path.push(&rule.name);
+        // Built from the default backwards, each condition decides between its target and the rest
+        let mut tree = DecisionTree::compile_result(workflows, &rule.default_rule, path, compiled)?;
+        for (condition, result) in rule.conditions.iter().rev() {
+            let target = DecisionTree::compile_result(workflows, result, path, compiled)?;
+            let rating = condition.rating();
+            tree = match condition.comparator {
+                '<' => Rc::new(DecisionTree::Split{rating, value: condition.value, below: target, above: tree}),
+                // Nothing is above the largest rating
+                _ if condition.value == u32::MAX => tree,
+                _ => Rc::new(DecisionTree::Split{rating, value: condition.value + 1, below: tree, above: target}),
+            };
         }
+        path.pop();
+        compiled.insert(&rule.name, tree.clone());
+        Ok(tree)
     }
-}
 
-fn match_interval(rules:&Vec<Rule>) -> usize {
-    let start_intervals = vec![('x', 1,4000),('m', 1,4000),('a', 1,4000),('s', 1,4000)];
-    let mut accepted = 0;
-    let mut queue = VecDeque::new();
-    queue.push_back((start_intervals, "in".to_string()));
-
-    while !queue.is_empty() {
-        let (intervals, rule_name) = queue.pop_front().unwrap();
-        let rule = rules.iter().find(|rule| rule.name.as_str().eq(rule_name.as_str())).unwrap();
-        let results = rule.match_interval(intervals);
-
-        for (interval, res) in results {
-            match res {
-                Accepted => {
-                    accepted += interval.iter().map(|(_, start, end)| *end - *start + 1)
-                        .map(|prod| prod as usize)
-                        .product::<usize>();
+    pub fn accepts(&self, part:&MachinePart) -> bool {
+        let ratings = part.ratings();
+        let mut tree = self;
+        loop {
+            match tree {
+                DecisionTree::Accept => return true,
+                DecisionTree::Reject => return false,
+                DecisionTree::Split{rating, value, below, above} => {
+                    tree = if ratings[*rating] < *value { below } else { above };
                 },
-                RuleResult::Rule(next_rule_name) => {
-                    queue.push_back((interval, next_rule_name));
+            }
+        }
+    }
+
+    /// Boxes of parts within `bounds` that are accepted, which don't overlap and together hold every
+    /// accepted part
+    pub fn accepted_ranges(&self, bounds:Ratings) -> Vec<Ratings> {
+        let mut accepted = vec![];
+        let mut stack = vec![(self, bounds)];
+        while let Some((tree, ranges)) = stack.pop() {
+            match tree {
+                DecisionTree::Accept => accepted.push(ranges),
+                DecisionTree::Reject => {},
+                DecisionTree::Split{rating, value, below, above} => {
+                    let (low, high) = ranges[*rating].split_at(*value as i64);
+                    for (tree, range) in [(below, low), (above, high)] {
+                        if !range.is_empty() {
+                            let mut split_ranges = ranges;
+                            split_ranges[*rating] = range;
+                            stack.push((tree, split_ranges));
+                        }
+                    }
                 },
-                _ => {},
             }
         }
+        accepted
     }
 
-    accepted
+    /// Number of distinct accepted parts with ratings within `bounds`
+    pub fn count_accepted(&self, bounds:Ratings) -> u128 {
+        self.accepted_ranges(bounds).iter()
+            .map(|ranges| ranges.iter().map(|range| range.len() as u128).product::<u128>())
+            .sum()
+    }
 }
 
-fn part1(input_rules : Vec<&str>, input_parts : Vec<&str>) -> String {
-    let parts:Vec<MachinePart> = input_parts.iter().map(|line| MachinePart::new(line)).collect();
-    let rules:Vec<Rule> = input_rules.into_iter().map(|s| Rule::new(s)).collect();
-
-    parts.iter()
-        .filter(|part| match_rule(part, &rules))
+fn part1(system:&System) -> Result<String> {
+    let tree = DecisionTree::compile(&system.rules)?;
+    let sum = system.parts.iter()
+        .filter(|part| tree.accepts(part))
         .map(|part| part.sum())
-        .sum::<u32>()
-        .to_string()
-}
+        .sum::<u32>();
 
-fn part2(input_rules : Vec<&str>, _input_parts : Vec<&str>) -> String {
-    let rules:Vec<Rule> = input_rules.into_iter().map(|s| Rule::new(s)).collect();
-    match_interval(&rules).to_string()
+    Ok(sum.to_string())
 }
 
+fn part2(system:&System) -> Result<String> {
+    let tree = DecisionTree::compile(&system.rules)?;
+    Ok(tree.count_accepted(ALL_RATINGS).to_string())
+}
 
-#[cfg(test)]
-mod tests {
-    use std::assert_eq;
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-    use util::Part::{Part1, Part2};
-
-
-    const INPUT:&str = "px{a<2006:qkq,m>2090:A,rfg}
+const EXAMPLE:&str = "px{a<2006:qkq,m>2090:A,rfg}
 pv{a>1716:R,A}
 lnx{m>1548:A,A}
 rfg{s<537:gd,x>2440:R,A}
@@ -260,25 +313,92 @@ hdj{m>838:A,pv}
 {x=2461,m=1339,a=466,s=291}
 {x=2127,m=1623,a=2188,s=1013}";
 
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "19114"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "167409079868000"},
+];
+
+
+#[cfg(test)]
+mod tests {
+    use std::assert_eq;
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
+
+
     #[test]
     fn test1() {
-        assert_eq!("19114", solve(INPUT.to_string(), Part1));
+        assert_eq!("19114", solve(EXAMPLE.to_string(), Part1).unwrap());
+    }
+
+    #[test]
+    fn test_decision_tree() {
+        let system = parse(EXAMPLE).unwrap();
+        let tree = DecisionTree::compile(&system.rules).unwrap();
+        assert_eq!(vec![true, false, true, false, true], system.parts.iter().map(|part| tree.accepts(part)).collect::<Vec<bool>>());
+
+        let ranges = tree.accepted_ranges(ALL_RATINGS);
+        assert!(ranges.iter().all(|ranges| {
+            let (first, last) = (ranges.map(|range| range.start as u32), ranges.map(|range| range.end as u32 - 1));
+            tree.accepts(&MachinePart{x: first[0], m: last[1], a: first[2], s: last[3]})
+        }));
+        assert_eq!(167409079868000, tree.count_accepted(ALL_RATINGS));
+
+        // Small enough to try every part, around the values the workflows split at
+        let bounds = [Interval::inclusive(1400, 1420), Interval::inclusive(830, 850), Interval::inclusive(2000, 2010), Interval::inclusive(1345, 1355)];
+        let mut accepted = 0;
+        for x in bounds[0].start as u32..bounds[0].end as u32 {
+            for m in bounds[1].start as u32..bounds[1].end as u32 {
+                for a in bounds[2].start as u32..bounds[2].end as u32 {
+                    for s in bounds[3].start as u32..bounds[3].end as u32 {
+                        accepted += tree.accepts(&MachinePart{x, m, a, s}) as u128;
+                    }
+                }
+            }
+        }
+        assert!(0 < accepted && accepted < 21 * 21 * 11 * 11);
+        assert_eq!(accepted, tree.count_accepted(bounds));
+        assert_eq!(0, tree.count_accepted([Interval::new(1, 1), ALL_RATINGS[1], ALL_RATINGS[2], ALL_RATINGS[3]]));
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        let compile = |workflows:&str| DecisionTree::compile(&parse(&format!("{}\n\n{{x=1,m=1,a=1,s=1}}", workflows)).unwrap().rules);
+        assert_eq!(Err(SolveError::unsolvable("workflows send a part around in a loop")), compile("in{x>1:aa,R}\naa{m>1:in,A}"));
+        assert_eq!(Err(SolveError::unsolvable("unknown workflow bb")), compile("in{x>1:aa,R}\naa{m>1:bb,A}"));
+        assert_eq!(Err(SolveError::unsolvable("unknown workflow in")), compile("aa{m>1:A,R}"));
+    }
+
+    #[test]
+    fn test_shared_workflows() {
+        // Every workflow sends both ways to the next, inlined that would be 2^40 leaves
+        let workflows = (0..40)
+            .map(|i| format!("{}{{x<{}:w{},w{}}}", if i == 0 { "in".to_string() } else { format!("w{}", i) }, i + 2, i + 1, i + 1))
+            .chain(Some("w40{m>2000:A,R}".to_string()))
+            .collect::<Vec<String>>()
+            .join("\n");
+        let tree = DecisionTree::compile(&parse(&format!("{}\n\n{{x=1,m=1,a=1,s=1}}", workflows)).unwrap().rules).unwrap();
+        assert!(tree.accepts(&MachinePart{x: 1, m: 2001, a: 1, s: 1}));
+        assert!(!tree.accepts(&MachinePart{x: 4000, m: 2000, a: 1, s: 1}));
+        assert_eq!(4000 * 2000 * 4000 * 4000, tree.count_accepted(ALL_RATINGS));
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_19.txt");
-        assert_eq!("319295", solve(input.to_string(), Part1));
+        assert_answer(19, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("167409079868000", solve(INPUT.to_string(), Part2));
+        assert_eq!("167409079868000", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_19.txt");
-        assert_eq!("110807725108076", solve(input.to_string(), Part2));
+        assert_answer(19, Part2, solve(input.to_string(), Part2));
     }
 }
@@ -1,308 +1,449 @@
-use std::collections::{HashMap, VecDeque};
-use super::Part;
+use std::cell::RefCell;
+use std::collections::VecDeque;
 
-pub fn solve(input : String, part: Part) -> String {
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+use super::math::checked_lcm_all;
+use super::{Example, Part, Puzzle, Result, SolveError, Solver};
+
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Vec<ModuleSpec>;
+
+    fn day(&self) -> u8 {
+        20
     }
-}
 
+    fn title(&self) -> &'static str {
+        "Pulse Propagation"
+    }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
-enum PulseValue {
-    High,
-    Low,
-}
+    fn parse(&self, input:&str) -> Result<Vec<ModuleSpec>> {
+        parse(input)
+    }
 
-#[derive(Debug)]
-struct Pulse {
-    src:String,
-    pulse_value:PulseValue,
-    dst:String,
-}
+    fn part1(&self, modules:&Vec<ModuleSpec>) -> Result<String> {
+        part1(modules)
+    }
 
-impl Pulse {
-    fn new(pulse_value:PulseValue, src:String, dst:String) -> Pulse {
-        Pulse{pulse_value, src, dst}
+    fn part2(&self, modules:&Vec<ModuleSpec>) -> Result<String> {
+        part2(modules)
     }
 
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
+    }
 }
 
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
 
 
-trait Processor {
-    // Takes a pulse as input and produces output
-    fn process(&mut self, pulse:Pulse) -> Vec<Pulse>;
-    fn get_name(&self) -> &String;
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ModuleKind {
+    Broadcaster,
+    FlipFlop,
+    Conjunction,
+}
+
+/// A module as written in the configuration, before it holds any state
+#[derive(Debug, Clone)]
+pub struct ModuleSpec {
+    pub name:String,
+    pub kind:ModuleKind,
+    pub outputs:Vec<String>,
+}
 
-    fn get_outputs(&self) -> &Vec<String>;
+pub fn parse(input:&str) -> Result<Vec<ModuleSpec>> {
+    let modules = input.lines()
+        .enumerate()
+        .map(|(index, line)| parse_line(index, line))
+        .collect::<Result<Vec<ModuleSpec>>>()?;
 
-    fn add_input(&mut self, input:&String);
+    if !modules.iter().any(|m| m.kind == ModuleKind::Broadcaster) {
+        return Err(SolveError::malformed(1, 1, "missing broadcaster module"));
+    }
+    Ok(modules)
 }
 
+fn parse_line(index:usize, line:&str) -> Result<ModuleSpec> {
+    let mut items:Vec<&str> = line.split([' ', ','])
+        .filter(|s| !s.is_empty() && !s.eq(&"->"))
+        .collect();
 
-#[derive(Debug)]
-struct BroadCaster {
-    name:String,
-    outputs:Vec<String>,
+    if items.is_empty() {
+        return Err(SolveError::malformed(index + 1, 1, "missing module"));
+    }
+
+    let module_desc = items.remove(0);
+    let outputs = items.iter().map(|s| s.to_string()).collect();
+    let (name, kind) = if module_desc.starts_with("broadcaster") {
+        (module_desc, ModuleKind::Broadcaster)
+    } else if let Some(name) = module_desc.strip_prefix('%') {
+        (name, ModuleKind::FlipFlop)
+    } else if let Some(name) = module_desc.strip_prefix('&') {
+        (name, ModuleKind::Conjunction)
+    } else {
+        return Err(SolveError::at_token(index, line, module_desc, format!("unknown module type '{}'", module_desc)));
+    };
+    Ok(ModuleSpec{name: name.to_string(), kind, outputs})
 }
 
-#[derive(Debug)]
-struct FlipFlop {
-    mode:bool,
-    name:String,
-    outputs:Vec<String>,
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum PulseValue {
+    High,
+    Low,
 }
 
-#[derive(Debug)]
-struct Conjunction {
-    inputs:Vec<(String, PulseValue)>,
-    name:String,
-    outputs:Vec<String>,
+/// A pulse sent from one module to another, modules are given by their index in the `Network`
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Pulse {
+    pub src:usize,
+    pub value:PulseValue,
+    pub dst:usize,
 }
 
-#[derive(Debug)]
-struct OutputNode {
-    name:String,
-    outputs:Vec<String>,
+/// State of every module and the number of button presses so far, to return a network to later
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    presses:u64,
+    /// Whether each flip-flop is on, false for other modules
+    flip_flops:Vec<bool>,
+    /// The last pulse each conjunction got from each of its inputs, empty for other modules
+    memories:Vec<Vec<PulseValue>>,
 }
 
-impl OutputNode {
-    fn new(name:&str) -> OutputNode {
-        OutputNode{name:name.to_string(), outputs:vec![]}
-    }
+type Hook<'a> = Box<dyn FnMut(u64, &Pulse) + 'a>;
+
+/// Simulates the modules of a configuration. Modules are numbered in the order they are configured,
+/// followed by the modules that only receive pulses and finally the button.
+pub struct Network<'a> {
+    names:Vec<String>,
+    /// None for the modules that only receive pulses and the button
+    kinds:Vec<Option<ModuleKind>>,
+    /// For each output of each module, the module and the number of the input it arrives at
+    outputs:Vec<Vec<(usize, usize)>>,
+    inputs:Vec<Vec<usize>>,
+    broadcaster:usize,
+    state:Snapshot,
+    /// Called for the pulses on the wire from the first module to the second
+    hooks:Vec<(usize, usize, Hook<'a>)>,
 }
 
-impl Processor for OutputNode {
-    fn process(&mut self, _pulse: Pulse) -> Vec<Pulse> {
-        vec![]
-    }
+impl<'a> Network<'a> {
+    pub fn new(specs:&[ModuleSpec]) -> Result<Network<'a>> {
+        let mut names = specs.iter().map(|spec| spec.name.clone()).collect::<Vec<String>>();
+        let mut kinds = specs.iter().map(|spec| Some(spec.kind)).collect::<Vec<Option<ModuleKind>>>();
+        for output in specs.iter().flat_map(|spec| spec.outputs.iter()) {
+            if !names.contains(output) {
+                names.push(output.clone());
+                kinds.push(None);
+            }
+        }
+        names.push(BUTTON.to_string());
+        kinds.push(None);
+        let index = |name:&str| names.iter().position(|other| other == name).unwrap();
+
+        let broadcaster = kinds.iter().position(|kind| *kind == Some(ModuleKind::Broadcaster))
+            .ok_or_else(|| SolveError::unsolvable("no broadcaster module"))?;
+        let mut outputs = vec![vec![]; names.len()];
+        let mut inputs = vec![vec![]; names.len()];
+        let wires = specs.iter()
+            .flat_map(|spec| spec.outputs.iter().map(move |output| (spec.name.as_str(), output.as_str())))
+            .map(|(src, dst)| (index(src), index(dst)))
+            .chain(Some((names.len() - 1, broadcaster)));
+        for (src, dst) in wires {
+            outputs[src].push((dst, inputs[dst].len()));
+            inputs[dst].push(src);
+        }
 
-    fn get_name(&self) -> &String {
-        &self.name
+        let memories = kinds.iter().enumerate()
+            .map(|(module, kind)| match kind {
+                Some(ModuleKind::Conjunction) => vec![PulseValue::Low; inputs[module].len()],
+                _ => vec![],
+            })
+            .collect();
+        let state = Snapshot{presses: 0, flip_flops: vec![false; names.len()], memories};
+        Ok(Network{names, kinds, outputs, inputs, broadcaster, state, hooks: vec![]})
     }
 
-    fn get_outputs(&self) -> &Vec<String> {
-        &self.outputs
+    pub fn len(&self) -> usize {
+        self.names.len()
     }
 
-    fn add_input(&mut self, _input: &String) {
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
     }
-}
 
-impl BroadCaster {
-    fn new(name:&str, outputs:Vec<&str>) -> BroadCaster {
-        BroadCaster{name:name.to_string(), outputs:outputs.iter().map(|s| s.to_string()).collect()}
+    pub fn index(&self, name:&str) -> Option<usize> {
+        self.names.iter().position(|other| other == name)
     }
-}
 
-impl FlipFlop {
-    fn new(name:&str, outputs:Vec<&str>) -> FlipFlop {
-        FlipFlop{mode:false, name:name.to_string(), outputs:outputs.iter().map(|s| s.to_string()).collect()}
+    pub fn name(&self, module:usize) -> &str {
+        &self.names[module]
     }
-}
 
-impl Conjunction {
-    fn new(name:&str, outputs:Vec<&str>) -> Conjunction {
-        Conjunction{inputs:vec![], name:name.to_string(), outputs:outputs.iter().map(|s| s.to_string()).collect()}
+    /// None for the modules that only receive pulses and the button
+    pub fn kind(&self, module:usize) -> Option<ModuleKind> {
+        self.kinds[module]
     }
-}
 
-impl Processor for  BroadCaster {
-    fn process(&mut self, _pulse: Pulse) -> Vec<Pulse> {
-        self.outputs.iter()
-            .map(|output| Pulse::new(PulseValue::Low, self.get_name().clone(), output.clone()))
-            .collect()
+    pub fn button(&self) -> usize {
+        self.names.len() - 1
     }
-    fn get_name(&self) -> &String {
-        &self.name
-    }
-    fn get_outputs(&self) -> &Vec<String> {
-        &self.outputs
-    }
-
-    fn add_input(&mut self, _input: &String) {
 
+    pub fn broadcaster(&self) -> usize {
+        self.broadcaster
     }
-}
 
-impl Processor for  Conjunction {
-    fn process(&mut self, pulse: Pulse) -> Vec<Pulse> {
+    pub fn inputs(&self, module:usize) -> &[usize] {
+        &self.inputs[module]
+    }
 
-        let item = self.inputs.iter_mut().find(|item| item.0.eq(&pulse.src)).unwrap();
-        item.1 = pulse.pulse_value;
+    pub fn outputs(&self, module:usize) -> impl Iterator<Item=usize> + '_ {
+        self.outputs[module].iter().map(|(dst, _)| *dst)
+    }
 
-        let all_high = self.inputs.iter().all(|item| item.1 == PulseValue::High);
-        let next_pulse = if all_high {
-            PulseValue::Low
-        } else {
-            PulseValue::High
-        };
+    pub fn presses(&self) -> u64 {
+        self.state.presses
+    }
 
-        self.outputs.iter()
-            .map(|output| Pulse::new(next_pulse, self.get_name().clone(), output.clone()))
-            .collect()
+    /// Calls `hook` with the number of the button press and the pulse, for every pulse from `src` to `dst`
+    pub fn on_pulse(&mut self, src:usize, dst:usize, hook:impl FnMut(u64, &Pulse) + 'a) {
+        self.hooks.push((src, dst, Box::new(hook)));
     }
-    fn get_name(&self) -> &String {
-        &self.name
+
+    pub fn snapshot(&self) -> Snapshot {
+        self.state.clone()
     }
 
-    fn get_outputs(&self) -> &Vec<String> {
-        &self.outputs
+    pub fn restore(&mut self, snapshot:&Snapshot) {
+        assert_eq!(self.names.len(), snapshot.flip_flops.len(), "snapshot of another network");
+        self.state = snapshot.clone();
     }
 
-    fn add_input(&mut self, input:&String) {
-        self.inputs.push((input.clone(), PulseValue::Low))
+    /// Every module back as it was before the first button press
+    pub fn reset(&mut self) {
+        self.state.presses = 0;
+        self.state.flip_flops.iter_mut().for_each(|on| *on = false);
+        self.state.memories.iter_mut().flatten().for_each(|memory| *memory = PulseValue::Low);
     }
-}
 
-impl Processor for FlipFlop {
-    fn process(&mut self, pulse: Pulse) -> Vec<Pulse> {
-        if pulse.pulse_value == PulseValue::Low {
-            self.mode = !self.mode;
-            let next_pulse = match self.mode {
-                true => PulseValue::High,
-                false => PulseValue::Low,
+    /// Pushes the button once and sends pulses until the network settles, every pulse in the order
+    /// it was sent, starting with the one from the button
+    pub fn press_button(&mut self) -> Vec<Pulse> {
+        self.state.presses += 1;
+        let mut trace = vec![];
+        let mut queue = VecDeque::from([(Pulse{src: self.button(), value: PulseValue::Low, dst: self.broadcaster}, 0)]);
+
+        while let Some((pulse, input)) = queue.pop_front() {
+            let presses = self.state.presses;
+            for (_, _, hook) in self.hooks.iter_mut().filter(|(src, dst, _)| *src == pulse.src && *dst == pulse.dst) {
+                hook(presses, &pulse);
+            }
+            trace.push(pulse);
+
+            let module = pulse.dst;
+            let value = match self.kinds[module] {
+                Some(ModuleKind::Broadcaster) => Some(pulse.value),
+                Some(ModuleKind::FlipFlop) if pulse.value == PulseValue::Low => {
+                    let on = &mut self.state.flip_flops[module];
+                    *on = !*on;
+                    Some(if *on { PulseValue::High } else { PulseValue::Low })
+                },
+                Some(ModuleKind::Conjunction) => {
+                    let memory = &mut self.state.memories[module];
+                    memory[input] = pulse.value;
+                    Some(if memory.iter().all(|value| *value == PulseValue::High) { PulseValue::Low } else { PulseValue::High })
+                },
+                _ => None,
             };
-            self.outputs.iter()
-                .map(|output| Pulse::new(next_pulse, self.get_name().clone(), output.clone()))
-                .collect()
-        } else {
-            vec![]
+
+            if let Some(value) = value {
+                queue.extend(self.outputs[module].iter().map(|(dst, input)| (Pulse{src: module, value, dst: *dst}, *input)));
+            }
         }
-    }
-    fn get_name(&self) -> &String {
-        &self.name
-    }
-    fn get_outputs(&self) -> &Vec<String> {
-        &self.outputs
+        trace
     }
 
-    fn add_input(&mut self, _in:&String) {
+    /// The modules and their wires as a Graphviz graph, with `with_states` the flip-flops that are on
+    /// are filled and labelled as such.
+    ///
+    /// The broadcaster is a double octagon, flip-flops are boxes, conjunctions are diamonds, the
+    /// button is a circle and modules that only receive pulses are plain text.
+    pub fn to_dot(&self, with_states:bool) -> String {
+        let mut dot = String::from("digraph modules {\n");
+        for module in 0..self.len() {
+            let shape = match self.kinds[module] {
+                Some(ModuleKind::Broadcaster) => "doubleoctagon",
+                Some(ModuleKind::FlipFlop) => "box",
+                Some(ModuleKind::Conjunction) => "diamond",
+                None if module == self.button() => "circle",
+                None => "plaintext",
+            };
+            let state = match self.kinds[module] {
+                Some(ModuleKind::FlipFlop) if with_states => match self.state.flip_flops[module] {
+                    true => format!(", label=\"{}\\non\", style=filled", dot_escape(&self.names[module])),
+                    false => format!(", label=\"{}\\noff\"", dot_escape(&self.names[module])),
+                },
+                _ => String::new(),
+            };
+            dot += &format!("    {} [shape={}{}];\n", dot_id(&self.names[module]), shape, state);
+        }
+        for module in 0..self.len() {
+            for output in self.outputs(module) {
+                dot += &format!("    {} -> {};\n", dot_id(&self.names[module]), dot_id(&self.names[output]));
+            }
+        }
+        dot + "}\n"
+    }
 
+    /// A pulse as the puzzle writes it, e.g. `broadcaster -low-> a`
+    pub fn describe(&self, pulse:&Pulse) -> String {
+        let value = if pulse.value == PulseValue::High { "high" } else { "low" };
+        format!("{} -{}-> {}", self.names[pulse.src], value, self.names[pulse.dst])
     }
 }
 
-
-fn parse_line(line:&str) -> Box<dyn Processor> {
-    let mut items:Vec<&str> = line.split(|c| c == ' ' || c == ',')
-        .filter(|s| !s.is_empty() && !s.eq(&"->"))
-        .collect();
-
-    let module_desc = items.remove(0);
-    let name = &module_desc[1..];
-    let outputs = items;
-    if module_desc.starts_with("broadcaster") {
-        Box::new(BroadCaster::new(module_desc, outputs))
-    } else if module_desc.starts_with("%") {
-        Box::new(FlipFlop::new(name, outputs))
-
-    } else if module_desc.starts_with("&") {
-        Box::new(Conjunction::new(name, outputs))
-    } else {
-        panic!("...")
-    }
+// A module name as a quoted Graphviz identifier
+fn dot_id(name:&str) -> String {
+    format!("\"{}\"", dot_escape(name))
 }
 
+// A module name escaped to go between the quotes of a Graphviz string
+fn dot_escape(name:&str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
 
-fn build_modules(lines : Vec<&str>) -> Vec<Box<dyn Processor>> {
-    let mut modules:Vec<Box< dyn Processor>> = lines.iter()
-        .map(|line| parse_line(line)).collect();
-    let output_pairs = modules.iter()
-        .map(|m | (m.get_name().clone(), m.get_outputs().clone()))
-        .collect::<Vec<(String, Vec<String>)>>();
+const BUTTON:&str = "button";
 
-    let outputs:Vec<&String> = output_pairs.iter().map(|(_name, outs)| outs).flatten().collect();
-    let output_name = outputs.iter().find(|output| modules.iter().all(|m| !m.get_name().as_str().eq(output.as_str()))).unwrap();
-    let output_node = OutputNode::new(output_name.as_str());
-    modules.push(Box::new(output_node));
+// Presses to wait at most for every input of the module feeding rx to repeat its high pulses
+const MAX_PRESSES:u64 = 100000;
 
+/// When an input of the conjunction feeding rx sends it high pulses: first at press `offset`, then
+/// every `period` presses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeederCycle {
+    pub module:usize,
+    pub offset:u64,
+    pub period:u64,
+}
 
-    for (src, outputs) in output_pairs.iter() {
-        for out in outputs {
-            let module = modules.iter_mut()
-                .find(|m| m.get_name().as_str().eq(out.as_str())).unwrap();
-            module.add_input(&src);
-        }
+/// The conjunction feeding rx, which sends rx a low pulse when all its inputs last sent it a high one
+fn rx_feeder(network:&Network) -> Result<usize> {
+    let rx = network.index("rx").ok_or_else(|| SolveError::unsolvable("no module sends pulses to rx"))?;
+    let feeder = match network.inputs(rx) {
+        [feeder] => *feeder,
+        inputs => return Err(SolveError::unsolvable(format!("expected a single module to feed rx, found {}", inputs.len()))),
+    };
+    if network.kind(feeder) != Some(ModuleKind::Conjunction) {
+        return Err(SolveError::unsolvable(format!("expected {} feeding rx to be a conjunction", network.name(feeder))));
     }
-    modules
+    Ok(feeder)
 }
 
-fn get_parent_node(node_name:&str, modules:&Vec<Box<dyn Processor>>) -> String {
-    for module in modules.iter() {
-        if module.get_outputs().iter().any(|out| out.as_str().eq(node_name)) {
-            return module.get_name().to_string();
+/// Presses the button until each input of the conjunction feeding rx has sent it high pulses at three
+/// presses, and derives their cycles from those.
+///
+/// Every input has to return to low within the press it sends a high pulse, and its high pulses have
+/// to be evenly spaced, otherwise the cycles say nothing about when they all line up.
+pub fn feeder_cycles(specs:&[ModuleSpec]) -> Result<Vec<FeederCycle>> {
+    // The presses each input sent a high pulse at, and the last pulse it sent
+    let highs = RefCell::new(vec![]);
+    let last = RefCell::new(vec![]);
+    let mut network = Network::new(specs)?;
+    let feeder = rx_feeder(&network)?;
+    let inputs = network.inputs(feeder).to_vec();
+    highs.replace(vec![vec![]; inputs.len()]);
+    last.replace(vec![PulseValue::Low; inputs.len()]);
+    for (i, input) in inputs.iter().copied().enumerate() {
+        let (highs, last) = (&highs, &last);
+        network.on_pulse(input, feeder, move |presses, pulse| {
+            let mut highs = highs.borrow_mut();
+            if pulse.value == PulseValue::High && highs[i].last() != Some(&presses) {
+                highs[i].push(presses);
+            }
+            last.borrow_mut()[i] = pulse.value;
+        });
+    }
+
+    while highs.borrow().iter().any(|presses| presses.len() < 3) {
+        if network.presses() == MAX_PRESSES {
+            let i = highs.borrow().iter().position(|presses| presses.len() < 3).unwrap();
+            return Err(SolveError::unsolvable(format!("{} sent {} fewer than three high pulses in {} presses",
+                                                      network.name(inputs[i]), network.name(feeder), MAX_PRESSES)));
+        }
+        network.press_button();
+        if let Some(i) = last.borrow().iter().position(|value| *value == PulseValue::High) {
+            return Err(SolveError::unsolvable(format!("{} is still high at the end of press {}",
+                                                      network.name(inputs[i]), network.presses())));
         }
     }
 
-    panic!("...")
+    let highs = highs.borrow();
+    inputs.iter().zip(highs.iter())
+        .map(|(input, presses)| {
+            let (offset, period) = (presses[0], presses[1] - presses[0]);
+            if presses[2] - presses[1] != period {
+                return Err(SolveError::unsolvable(format!("{} sent high pulses at presses {}, {} and {}, which are not evenly spaced",
+                                                          network.name(*input), presses[0], presses[1], presses[2])));
+            }
+            Ok(FeederCycle{module: *input, offset, period})
+        })
+        .collect()
 }
 
-fn part1(lines : Vec<&str>) -> String {
-    let mut modules = build_modules(lines);
-    let mut queue = VecDeque::new();
+fn part1(specs:&[ModuleSpec]) -> Result<String> {
+    let mut network = Network::new(specs)?;
     let mut high_pulses = 0;
     let mut low_pulses = 0;
 
     for _ in 0..1000 {
-        queue.push_front(Pulse::new(PulseValue::Low, String::from("aptly"), String::from("broadcaster")));
-
-        // Run
-        while !queue.is_empty() {
-            let pulse = queue.pop_front().unwrap();
-
-            if pulse.pulse_value == PulseValue::High {
+        for pulse in network.press_button() {
+            if pulse.value == PulseValue::High {
                 high_pulses += 1;
             } else {
                 low_pulses += 1;
             }
-
-            // Find destination module
-            let module = modules.iter_mut().find(|m| m.get_name().eq(&pulse.dst)).unwrap();
-
-            module.process(pulse).into_iter()
-                .for_each(|out_pulse| queue.push_back(out_pulse));
-
         }
     }
 
-    (low_pulses * high_pulses).to_string()
+    Ok((low_pulses * high_pulses).to_string())
 }
 
-fn part2(lines : Vec<&str>) -> String {
-    let mut modules = build_modules(lines);
-    let mut queue = VecDeque::new();
-    let parent_node = get_parent_node("rx", &modules);
-    let mut parent_inputs = HashMap::new();
-
-    for i in 1..5000 {
-        queue.push_front(Pulse::new(PulseValue::Low, String::from("aptly"), String::from("broadcaster")));
-
-        // Run
-        while !queue.is_empty() {
-            let pulse = queue.pop_front().unwrap();
-
-            if pulse.dst.as_str().eq(parent_node.as_str()) {
-                if pulse.pulse_value == PulseValue::High {
-                    if !parent_inputs.contains_key(&pulse.src) {
-                        parent_inputs.insert(pulse.src.to_string(), i as u64);
-                    }
-                }
-            }
-
-            let module = modules.iter_mut().find(|m| m.get_name().eq(&pulse.dst)).unwrap();
-            let pulses = module.process(pulse);
-            for p in pulses {
-                queue.push_back(p);
-            }
-        }
+fn part2(specs:&[ModuleSpec]) -> Result<String> {
+    // rx gets a low pulse once every input sends a high pulse in the same press, which is the least
+    // common multiple of their periods as long as each cycle starts at its period
+    let cycles = feeder_cycles(specs)?;
+    if let Some(cycle) = cycles.iter().find(|cycle| cycle.offset != cycle.period) {
+        let network = Network::new(specs)?;
+        return Err(SolveError::unsolvable(format!("{} first sends a high pulse at press {} but repeats every {}, so the cycles don't line up at their least common multiple",
+                                                  network.name(cycle.module), cycle.offset, cycle.period)));
     }
 
-    parent_inputs.values().product::<u64>().to_string()
+    let presses = checked_lcm_all(cycles.iter().map(|cycle| cycle.period))
+        .ok_or_else(|| SolveError::unsolvable("the press every cycle lines up on is too large"))?;
+    Ok(presses.to_string())
 }
 
+const EXAMPLE:&str = "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output";
+
+const EXAMPLE_CYCLE:&str = "broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a";
+
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE_CYCLE, expected: "32000000"},
+    Example{part: Part::Part1, input: EXAMPLE, expected: "11687500"},
+];
+
 
 #[cfg(test)]
 mod tests {
@@ -310,33 +451,109 @@ mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     use util::Part::{Part1, Part2};
-
-
-    const INPUT:&str = "broadcaster -> a
-%a -> inv, con
-&inv -> b
-%b -> con
-&con -> output";
+    use util::answers::assert_answer;
 
 
     #[test]
     fn test1() {
-        assert_eq!("11687500", solve(INPUT.to_string(), Part1));
+        assert_eq!("32000000", solve(EXAMPLE_CYCLE.to_string(), Part1).unwrap());
+        assert_eq!("11687500", solve(EXAMPLE.to_string(), Part1).unwrap());
+    }
+
+    #[test]
+    fn test_press_button() {
+        let specs = parse(EXAMPLE).unwrap();
+        let mut network = Network::new(&specs).unwrap();
+        assert_eq!((7, Some(5), Some(ModuleKind::Conjunction), None), (network.len(), network.index("output"), network.kind(4), network.kind(5)));
+
+        let describe = |network:&Network, trace:Vec<Pulse>| trace.iter().map(|pulse| network.describe(pulse)).collect::<Vec<String>>();
+        let first = network.press_button();
+        assert_eq!(vec!["button -low-> broadcaster", "broadcaster -low-> a", "a -high-> inv", "a -high-> con",
+                        "inv -low-> b", "con -high-> output", "b -high-> con", "con -low-> output"],
+                   describe(&network, first.clone()));
+
+        let snapshot = network.snapshot();
+        let trace = network.press_button();
+        let second = describe(&network, trace);
+        assert_eq!(vec!["button -low-> broadcaster", "broadcaster -low-> a", "a -low-> inv", "a -low-> con",
+                        "inv -high-> b", "con -high-> output"], second);
+        network.restore(&snapshot);
+        let trace = network.press_button();
+        assert_eq!(second, describe(&network, trace));
+        assert_eq!(2, network.presses());
+
+        network.reset();
+        assert_eq!(first, network.press_button());
     }
 
+    #[test]
+    fn test_on_pulse() {
+        let specs = parse(EXAMPLE).unwrap();
+        let seen = RefCell::new(vec![]);
+        let mut network = Network::new(&specs).unwrap();
+        let (con, output) = (network.index("con").unwrap(), network.index("output").unwrap());
+        network.on_pulse(con, output, |presses, pulse| seen.borrow_mut().push((presses, pulse.value)));
+        for _ in 0..3 {
+            network.press_button();
+        }
+        drop(network);
+        assert_eq!(vec![(1, PulseValue::High), (1, PulseValue::Low), (2, PulseValue::High),
+                        (3, PulseValue::Low), (3, PulseValue::High)], seen.into_inner());
+    }
 
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_20.txt");
-        assert_eq!("898731036", solve(input.to_string(), Part1));
+        assert_answer(20, Part1, solve(input.to_string(), Part1));
     }
 
 
+    #[test]
+    fn test_to_dot() {
+        let specs = parse(EXAMPLE).unwrap();
+        let mut network = Network::new(&specs).unwrap();
+        let dot = network.to_dot(false);
+        assert!(dot.starts_with("digraph modules {\n    \"broadcaster\" [shape=doubleoctagon];\n    \"a\" [shape=box];\n"));
+        assert!(dot.contains("    \"con\" [shape=diamond];\n    \"output\" [shape=plaintext];\n    \"button\" [shape=circle];\n"));
+        assert!(dot.ends_with("    \"con\" -> \"output\";\n    \"button\" -> \"broadcaster\";\n}\n"));
+        assert_eq!(7, dot.matches(" -> ").count());
+
+        network.press_button();
+        let dot = network.to_dot(true);
+        assert!(dot.contains("    \"a\" [shape=box, label=\"a\\non\", style=filled];\n"));
+        assert!(dot.contains("    \"b\" [shape=box, label=\"b\\non\", style=filled];\n"));
+        network.press_button();
+        assert!(network.to_dot(true).contains("    \"a\" [shape=box, label=\"a\\noff\"];\n"));
+
+        // Names that would end the quotes early or escape the next character
+        let network = Network::new(&parse("broadcaster -> q\"\n%q\" -> back\\slash").unwrap()).unwrap();
+        let dot = network.to_dot(true);
+        assert!(dot.contains("    \"q\\\"\" [shape=box, label=\"q\\\"\\noff\"];\n"));
+        assert!(dot.contains("    \"q\\\"\" -> \"back\\\\slash\";\n"));
+    }
+
+    #[test]
+    fn test_feeder_cycles() {
+        let input = include_str!("../../input/input_20.txt");
+        let cycles = feeder_cycles(&parse(input).unwrap()).unwrap();
+        assert_eq!(4, cycles.len());
+        assert!(cycles.iter().all(|cycle| cycle.offset == cycle.period));
+
+        let error = |input:&str| feeder_cycles(&parse(input).unwrap()).unwrap_err();
+        assert_eq!(SolveError::unsolvable("no module sends pulses to rx"), error(EXAMPLE));
+        assert_eq!(SolveError::unsolvable("expected a single module to feed rx, found 2"), error("broadcaster -> a, rx\n%a -> rx"));
+        assert_eq!(SolveError::unsolvable("expected a feeding rx to be a conjunction"), error("broadcaster -> a\n%a -> rx"));
+        // The flip-flop stays on after the first press
+        assert_eq!(SolveError::unsolvable("a is still high at the end of press 1"), error("broadcaster -> a\n%a -> con\n&con -> rx"));
+        // The broadcaster only ever sends low pulses
+        assert_eq!(SolveError::unsolvable("broadcaster sent con fewer than three high pulses in 100000 presses"),
+                   error("broadcaster -> con\n&con -> rx"));
+    }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_20.txt");
-        assert_eq!("229414480926893", solve(input.to_string(), Part2));
+        assert_answer(20, Part2, solve(input.to_string(), Part2));
     }
 }
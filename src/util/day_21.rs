@@ -1,92 +1,163 @@
-use std::collections::{HashSet, VecDeque};
-use super::Part;
+use super::grid::{Direction, Grid, Pos};
+use super::search;
+use super::{Example, Part, Puzzle, Result, SolveError, Solver};
 
 
-pub fn solve(input : String, part: Part) -> String {
+pub struct Solution;
 
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+impl Puzzle for Solution {
+    type Input = Garden;
+
+    fn day(&self) -> u8 {
+        21
     }
-}
 
+    fn title(&self) -> &'static str {
+        "Step Counter"
+    }
 
-fn parse(lines:Vec<&str>) -> Vec<Vec<char>> {
-    lines.iter().map(|line| line.chars().collect()).collect()
-}
+    fn parse(&self, input:&str) -> Result<Garden> {
+        parse(input)
+    }
 
-fn get_start_pos(map:&Vec<Vec<char>>) -> (usize, usize) {
-    for y in 0..map.len() {
-        for x in 0..map[0].len() {
-            if map[y][x] == 'S' {
-                return (x,y)
-            }
-        }
+    fn part1(&self, garden:&Garden) -> Result<String> {
+        part1(garden)
+    }
+
+    fn part2(&self, garden:&Garden) -> Result<String> {
+        part2(garden)
     }
 
-    panic!("...")
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
+    }
+}
+
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
 }
 
 
-fn calc_steps(map:&Vec<Vec<char>>, no_steps:u32) -> usize {
-    let mut queue = VecDeque::new();
-    let mut visited = HashSet::new();
-    let width = map[0].len();
-    let height = map.len();
-    let mut visited_plots = 0;
-    let (x0, y0) = get_start_pos(&map);
+/// Map of garden plots and rocks with the position the elf starts from
+pub struct Garden {
+    pub map:Grid<char>,
+    pub start:Pos,
+}
 
-    queue.push_back((x0, y0, 0));
+pub fn parse(input:&str) -> Result<Garden> {
+    let map = Grid::parse_chars(input, ".#S")?;
+    let start = map.find(|ch| *ch == 'S')
+        .ok_or_else(|| SolveError::malformed(1, 1, "missing start position 'S'"))?;
+    Ok(Garden{map, start})
+}
 
-    while !queue.is_empty() {
-        let item = queue.pop_front().unwrap();
-        let (x, y, steps) = item;
 
-        if map[y][x] == '#' || visited.contains(&item) || steps > no_steps {
-            continue;
-        } else if steps == no_steps {
-            visited_plots += 1;
-        }
+// Plots reachable in exactly `no_steps` steps, those an even or odd number of steps away depending on
+// `no_steps` as the elf can step back and forth
+fn calc_steps(garden:&Garden, no_steps:usize) -> usize {
+    let map = &garden.map;
+    let search = search::bfs(vec![garden.start], |pos| map.neighbors4(*pos)
+        .filter(|next| map[*next] != '#')
+        .collect::<Vec<Pos>>());
 
-        visited.insert(item);
+    search.distances.values()
+        .filter(|distance| **distance <= no_steps && **distance % 2 == no_steps % 2)
+        .count()
+}
 
-        if x > 0 {
-            queue.push_back((x-1,y,steps + 1));
-        }
-        if x < width - 1 {
-            queue.push_back((x+1,y,steps + 1));
-        }
-        if y > 0 {
-            queue.push_back((x,y-1,steps + 1));
-        }
+/// Plots reachable in exactly `no_steps` steps when the garden repeats infinitely in every direction,
+/// found by visiting every plot within reach
+pub fn tiled_steps(garden:&Garden, no_steps:usize) -> u64 {
+    tiled_counts(garden, &[no_steps])[0]
+}
 
-        if y < height - 1 {
-            queue.push_back((x,y+1,steps + 1));
+// Plots reachable on the tiled garden for each of the step counts, from a single search as far as the largest
+fn tiled_counts(garden:&Garden, step_counts:&[usize]) -> Vec<u64> {
+    let map = &garden.map;
+    let max_steps = step_counts.iter().copied().max().unwrap_or(0);
+    let is_plot = |(x, y):(isize, isize)| {
+        let tile_x = (garden.start.0 as isize + x).rem_euclid(map.width() as isize) as usize;
+        let tile_y = (garden.start.1 as isize + y).rem_euclid(map.height() as isize) as usize;
+        map[(tile_x, tile_y)] != '#'
+    };
+
+    // Searched one step at a time, counting the plots first reached at each step. A neighbour of a plot
+    // is always one step nearer or one step further, so only the plots of the step before are needed
+    // to tell the new ones apart, kept sorted to look them up. Memory grows with the step count rather
+    // than its square.
+    let mut layer_sizes = vec![1];
+    let mut previous = vec![];
+    let mut current = vec![(0, 0)];
+    for _ in 0..max_steps {
+        let mut next = vec![];
+        for (x, y) in current.iter() {
+            for (dx, dy) in IntoIterator::into_iter(Direction::ALL).map(|direction| direction.delta()) {
+                let plot = (x + dx, y + dy);
+                if is_plot(plot) && previous.binary_search(&plot).is_err() {
+                    next.push(plot);
+                }
+            }
         }
+        next.sort_unstable();
+        next.dedup();
+        layer_sizes.push(next.len() as u64);
+        previous = current;
+        current = next;
     }
 
-    visited_plots
+    step_counts.iter()
+        .map(|no_steps| layer_sizes.iter().take(no_steps + 1).skip(no_steps % 2).step_by(2).sum())
+        .collect()
 }
 
-fn part1(lines:Vec<&str>) -> String {
-    let map = parse(lines);
-    let positions = calc_steps(&map, 64);
-    positions.to_string()
+/// Plots reachable in exactly `no_steps` steps on the infinitely repeating garden, for step counts too
+/// large to visit every plot.
+///
+/// Once the reachable area spans a few copies of the garden, walking two garden widths further adds the
+/// same pattern of copies every time, so the count is a quadratic in the number of such periods walked.
+/// The quadratic is fitted to three counts from the tiled search and checked against a fourth, starting
+/// a period later whenever the check fails.
+pub fn tiled_steps_extrapolated(garden:&Garden, no_steps:usize) -> Result<u64> {
+    let size = garden.map.width();
+    if size != garden.map.height() {
+        return Err(SolveError::unsolvable("the garden must be square to extrapolate over copies of it"));
+    }
+
+    // With an odd width neighbouring copies are reached on opposite parities, so the pattern repeats
+    // every other copy
+    let period = 2 * size;
+    for warmup in 0..=MAX_WARMUP_PERIODS {
+        let first = no_steps % period + warmup * period;
+        if no_steps <= first + 3 * period {
+            return Ok(tiled_steps(garden, no_steps));
+        }
+
+        let counts = tiled_counts(garden, &[first, first + period, first + 2 * period, first + 3 * period]);
+        let counts = counts.into_iter().map(|count| count as i64).collect::<Vec<i64>>();
+        let (first_difference, second_difference) = (counts[1] - counts[0], counts[2] - 2 * counts[1] + counts[0]);
+        let quadratic = |n:i64| counts[0] + n * first_difference + n * (n - 1) / 2 * second_difference;
+        if quadratic(3) == counts[3] {
+            return Ok(quadratic(((no_steps - first) / period) as i64) as u64);
+        }
+    }
+    Err(SolveError::unsolvable("reachable plots don't grow quadratically over copies of the garden"))
 }
 
-fn part2(_lines:Vec<&str>) -> String {
-    "2".to_string()
+// Periods walked before giving up on the reachable area settling into a steady pattern
+const MAX_WARMUP_PERIODS:usize = 4;
+
+fn part1(garden:&Garden) -> Result<String> {
+    let positions = calc_steps(garden, 64);
+    Ok(positions.to_string())
 }
 
+fn part2(garden:&Garden) -> Result<String> {
+    let positions = tiled_steps_extrapolated(garden, 26501365)?;
+    Ok(positions.to_string())
+}
 
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-    use util::Part::{Part1, Part2};
 
-    const INPUT:&str = "...........
+const EXAMPLE:&str = "...........
 .....###.#.
 .###.##..#.
 ..#.#...#..
@@ -98,25 +169,51 @@ mod tests {
 .##..##.##.
 ...........";
 
+const EXAMPLES:[Example;1] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "42"},
+];
+
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
+
+
     #[test]
     fn test1() {
-        assert_eq!("42", solve(INPUT.to_string(), Part1));
+        assert_eq!("42", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_21.txt");
-        assert_eq!("3729", solve(input.to_string(), Part1));
+        assert_answer(21, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("2", solve(INPUT.to_string(), Part2));
+        let garden = parse(EXAMPLE).unwrap();
+        for (no_steps, plots) in [(6, 16), (10, 50), (50, 1594), (100, 6536), (500, 167004)] {
+            assert_eq!(plots, tiled_steps(&garden, no_steps));
+        }
+        for (no_steps, plots) in [(500, 167004), (1000, 668697), (5000, 16733044)] {
+            assert_eq!(Ok(plots), tiled_steps_extrapolated(&garden, no_steps));
+        }
+    }
+
+    #[test]
+    fn test_extrapolation() {
+        // A step count just large enough to extrapolate, yet small enough to count every plot
+        let garden = parse(include_str!("../../input/input_21.txt")).unwrap();
+        assert_eq!(Ok(tiled_steps(&garden, 1113)), tiled_steps_extrapolated(&garden, 1113));
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_21.txt");
-        assert_eq!("2", solve(input.to_string(), Part2));
+        assert_answer(21, Part2, solve(input.to_string(), Part2));
     }
 }
@@ -1,20 +1,46 @@
 use std::cmp::{max, min};
 use std::collections::{HashMap, HashSet, VecDeque};
-use super::Part;
+use super::parallel;
+use super::{parse_token, Example, Part, Puzzle, Result, SolveError, Solver};
 
 
-pub fn solve(input : String, part: Part) -> String {
+pub struct Solution;
 
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+impl Puzzle for Solution {
+    type Input = Settled;
+
+    fn day(&self) -> u8 {
+        22
+    }
+
+    fn title(&self) -> &'static str {
+        "Sand Slabs"
+    }
+
+    fn parse(&self, input:&str) -> Result<Settled> {
+        Ok(settle(&parse(input)?))
+    }
+
+    fn part1(&self, settled:&Settled) -> Result<String> {
+        part1(settled)
+    }
+
+    fn part2(&self, settled:&Settled) -> Result<String> {
+        part2(settled)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
     }
 }
 
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
 
-#[derive(Debug,Hash, Eq, PartialEq)]
-struct Point {
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Point {
     x:i32,
     y:i32,
     z:i32,
@@ -26,22 +52,27 @@ impl Point {
     }
 }
 
-#[derive(Debug, Hash, Eq, PartialEq)]
-struct Brick {
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Brick {
     name:u32,
     p1:Point,
     p2:Point,
 }
 
 impl Brick {
-    fn new(line:&str, name:u32) -> Brick {
-        let mut it = line
-            .split(|c| c == ',' || c == '~')
+    fn new(line:&str, name:u32) -> Result<Brick> {
+        let index = name as usize;
+        let coordinates = line
+            .split([',', '~'])
             .filter(|s| !s.is_empty())
-            .map(|s| s.parse::<i32>().unwrap());
-        let p1 = Point::new(it.next().unwrap(), it.next().unwrap(), it.next().unwrap());
-        let p2 = Point::new(it.next().unwrap(), it.next().unwrap(), it.next().unwrap());
-        Brick{name, p1, p2}
+            .map(|s| parse_token::<i32>(index, line, s))
+            .collect::<Result<Vec<i32>>>()?;
+        if coordinates.len() != 6 {
+            return Err(SolveError::malformed(index + 1, 1, "expected 'x,y,z~x,y,z'"));
+        }
+        let p1 = Point::new(coordinates[0], coordinates[1], coordinates[2]);
+        let p2 = Point::new(coordinates[3], coordinates[4], coordinates[5]);
+        Ok(Brick{name, p1, p2})
     }
 
 
@@ -50,7 +81,7 @@ impl Brick {
         self.p2.z -= units;
     }
 
-    fn supported_by<'a>(&'a self, bricks:&'a VecDeque<Brick>) -> Vec<&Brick> {
+    fn supported_by<'a>(&'a self, bricks:&'a [Brick]) -> Vec<&'a Brick> {
         let mut support = vec![];
         if self.min_z() > 1 {
             for brick in bricks.iter() {
@@ -137,20 +168,25 @@ impl Brick {
 }
 
 
-fn parse_bricks(lines:Vec<&str>) -> Vec<Brick> {
-    let mut i = 0;
-    let mut bricks = vec![];
-    for line in lines {
-        bricks.push(Brick::new(line, i));
-        i += 1;
-    }
-    bricks
+/// Reads the snapshot of the falling bricks, `settle` lets them fall
+pub fn parse(input:&str) -> Result<Vec<Brick>> {
+    input.lines()
+        .enumerate()
+        .map(|(i, line)| Brick::new(line, i as u32))
+        .collect()
 }
 
 
-fn arrange_bricks(lines:Vec<&str>) -> VecDeque<Brick> {
-    let mut bricks = parse_bricks(lines).into_iter().collect::<Vec<Brick>>();
-    bricks.sort_by( |a,b| a.min_z().cmp(&b.min_z()));
+/// The bricks after they all fell as far as they can, the model both parts work on
+#[derive(Debug, Clone)]
+pub struct Settled {
+    pub bricks:Vec<Brick>,
+}
+
+/// Lets every brick of the snapshot fall until the stack has settled
+pub fn settle(snapshot:&[Brick]) -> Settled {
+    let mut bricks = snapshot.to_vec();
+    bricks.sort_by_key(|a| a.min_z());
     let mut bricks = bricks.into_iter().collect::<VecDeque<Brick>>();
     let mut fell = true;
 
@@ -168,18 +204,16 @@ fn arrange_bricks(lines:Vec<&str>) -> VecDeque<Brick> {
             bricks.push_back(brick);
         }
     }
-    bricks
+    Settled{bricks: bricks.into_iter().collect()}
 }
 
-fn part1(lines:Vec<&str>) -> String {
-    let bricks = arrange_bricks(lines);
-
-    // Bricks are now arranged
+fn part1(settled:&Settled) -> Result<String> {
+    let bricks = &settled.bricks;
     let mut next:HashSet<&Brick> = bricks.iter().collect();
 
     for brick in bricks.iter() {
         // Does this brick has single support?
-        let support = brick.supported_by(&bricks);
+        let support = brick.supported_by(bricks);
 
         if support.len() == 1 {
             //println!("{} is only supported by {} so {} cant be removed.", brick.name, support[0].name, support[0].name);
@@ -187,23 +221,21 @@ fn part1(lines:Vec<&str>) -> String {
         }
     }
 
-    next.len().to_string()
+    Ok(next.len().to_string())
 }
 
 
-fn part2(lines:Vec<&str>) -> String {
-    let bricks = arrange_bricks(lines);
-
+fn part2(settled:&Settled) -> Result<String> {
+    let bricks = &settled.bricks;
     let mut upstream:HashMap<&Brick, Vec<&Brick>> = HashMap::new();
     let mut downstream:HashMap<&Brick,Vec<&Brick>> = HashMap::new();
 
-    let mut sum = 0 ;
     for brick in bricks.iter() {
         // What bricks are supporting this brick
-        let support_bricks = brick.supported_by(&bricks);
+        let support_bricks = brick.supported_by(bricks);
 
         // Add do down steam map
-        downstream.insert(&brick, support_bricks.iter().copied().map(|b| b).collect::<Vec<&Brick>>());
+        downstream.insert(brick, support_bricks.to_vec());
 
         // Add to upstream map
         for support_brick in support_bricks {
@@ -215,27 +247,23 @@ fn part2(lines:Vec<&str>) -> String {
         }
     }
 
-    for i in 0..bricks.len()  {
-        let brick = &bricks[i];
-        // check support
-        let num = check_support(brick, &downstream, &upstream, &mut vec![]);
-        sum += num;
-    }
+    // check support
+    let sum = parallel::map(bricks, |brick| check_support(brick, &downstream, &upstream, &mut vec![]))
+        .into_iter()
+        .sum::<u32>();
 
 
-    sum.to_string()
+    Ok(sum.to_string())
 }
 
 fn check_support<'a>(brick:&'a Brick, downstream: &HashMap<&Brick, Vec<&Brick>>, upstream: &HashMap<&Brick, Vec<&'a Brick>>, acc:&mut Vec<&'a Brick>) -> u32{
     let mut sum = 0;
     acc.push(brick);
 
-    let item = upstream.get(&brick);
-    if item.is_some() {
-        let list = item.unwrap();
+    if let Some(list) = upstream.get(&brick) {
         for brick_above in list.iter() {
-            if !acc.contains(brick_above) && !has_support(*brick_above, downstream, acc) {
-                sum += 1 + check_support(*brick_above, downstream, upstream, acc)
+            if !acc.contains(brick_above) && !has_support(brick_above, downstream, acc) {
+                sum += 1 + check_support(brick_above, downstream, upstream, acc)
             }
         }
     }
@@ -244,27 +272,21 @@ fn check_support<'a>(brick:&'a Brick, downstream: &HashMap<&Brick, Vec<&Brick>>,
 }
 
 
-fn has_support(brick:&Brick, downstream: &HashMap<&Brick, Vec<&Brick>>, acc:&Vec<&Brick>) -> bool {
+fn has_support(brick:&Brick, downstream: &HashMap<&Brick, Vec<&Brick>>, acc:&[&Brick]) -> bool {
     if brick.min_z() == 1 {
-        return true;
+        true
     } else {
         let support_bricks = downstream.get(brick).unwrap().iter()
             .filter(|c| !acc.contains(c))
             .copied()
             .collect::<Vec<&Brick>>();
 
-        support_bricks.iter().any(|support| has_support(*support, downstream, acc))
+        support_bricks.iter().any(|support| has_support(support, downstream, acc))
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use std::assert_eq;
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-    use util::Part::{Part1, Part2};
 
-    const INPUT:&str = "1,0,1~1,2,1
+const EXAMPLE:&str = "1,0,1~1,2,1
 0,0,2~2,0,2
 0,2,3~2,2,3
 0,0,4~0,2,4
@@ -272,25 +294,40 @@ mod tests {
 0,1,6~2,1,6
 1,1,8~1,1,9";
 
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "5"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "7"},
+];
+
+
+#[cfg(test)]
+mod tests {
+    use std::assert_eq;
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
+
+
     #[test]
     fn test1() {
-        assert_eq!("5", solve(INPUT.to_string(), Part1));
+        assert_eq!("5", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_22.txt");
-        assert_eq!("451", solve(input.to_string(), Part1));
+        assert_answer(22, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("7", solve(INPUT.to_string(), Part2));
+        assert_eq!("7", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_22.txt");
-        assert_eq!("66530", solve(input.to_string(), Part2));
+        assert_answer(22, Part2, solve(input.to_string(), Part2));
     }
 }
@@ -1,135 +1,187 @@
-use std::collections::{HashSet, VecDeque};
-use super::Part;
+use std::fmt;
 
+use super::grid::{Direction, Grid, Pos};
+use super::{Example, Part, Puzzle, Result, SolveError, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
 
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Grid<char>;
+
+    fn day(&self) -> u8 {
+        23
     }
-}
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
-enum Direction {
-    Left,
-    Right,
-    Down,
-    Up,
-}
+    fn title(&self) -> &'static str {
+        "A Long Walk"
+    }
 
-impl Direction {
-    fn get_delta(&self) -> (i32, i32) {
-        match *self {
-            Direction::Left => (-1,0),
-            Direction::Right => (1,0),
-            Direction::Down => (0,1),
-            Direction::Up => (0,-1),
-        }
+    fn parse(&self, input:&str) -> Result<Grid<char>> {
+        parse(input)
     }
 
-    fn get_next_pos(&self, pos: (i32, i32)) -> (i32, i32) {
-        let (dx, dy) = self.get_delta();
-        (dx + pos.0, dy + pos.1)
-    }
-
-    fn is_next_valid(&self, pos: (i32, i32), map:&Vec<Vec<char>>, part2:bool ) -> bool {
-        if pos.1 == 0 && *self == Direction::Up || pos.1 == map.len() as i32 - 1 && *self == Direction::Down {
-            false
-        } else {
-            let (next_x, next_y) = self.get_next_pos(pos);
-            let next_ch = map[next_y as usize][next_x as usize];
-            if part2 {
-                match next_ch {
-                    '#' => false,
-                    _ => true,
-                }
-            } else {
-                match next_ch {
-                    '.' => true,
-                    '>' => *self == Direction::Right,
-                    'v' => *self == Direction::Down,
-                    '<' => *self == Direction::Left,
-                    '^' => *self == Direction::Up,
-                    _ => false,
-                }
-            }
+    fn part1(&self, map:&Grid<char>) -> Result<String> {
+        part1(map)
+    }
 
-        }
+    fn part2(&self, map:&Grid<char>) -> Result<String> {
+        part2(map)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
     }
 }
 
-fn parse(lines:&Vec<&str>) -> Vec<Vec<char>> {
-    lines.iter().map(|line| line.chars().collect()).collect()
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
 }
 
-fn dfs(map:&Vec<Vec<char>>, part2:bool) -> usize {
-    let mut queue = VecDeque::new();
-    let start = (1, 0);
-    let end = (map[0].len() as i32 - 2, map.len() as i32 - 1);
+// Position one step in `direction`, if it can be walked onto; slopes can only be walked downhill unless `part2`
+fn next_valid(map:&Grid<char>, pos:Pos, direction:Direction, part2:bool) -> Option<Pos> {
+    let next = map.step(pos, direction)?;
+    let next_ch = map[next];
+    let valid = if part2 {
+        next_ch != '#'
+    } else {
+        match next_ch {
+            '.' => true,
+            '>' => direction == Direction::Right,
+            'v' => direction == Direction::Down,
+            '<' => direction == Direction::Left,
+            '^' => direction == Direction::Up,
+            _ => false,
+        }
+    };
+    Some(next).filter(|_| valid)
+}
 
-    let mut max_len = 0;
-    queue.push_back((HashSet::new(), start));
+pub fn parse(input:&str) -> Result<Grid<char>> {
+    let map = Grid::parse_chars(input, "#.><v^")?;
+    if map.height() < 2 || map.width() < 3 {
+        return Err(SolveError::malformed(1, 1, "expected a map with at least two rows and three columns"));
+    }
+    Ok(map)
+}
 
-    while !queue.is_empty() {
+/// Junctions of the trails, where a hike has a choice of ways to go, joined by the trails between them.
+/// The start and the end count as junctions too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrailGraph {
+    /// Position of each junction, the start first, the end second and the rest row by row
+    pub junctions:Vec<Pos>,
+    /// The trails leaving each junction, as the junction they lead to and their length in steps
+    pub trails:Vec<Vec<(usize, usize)>>,
+}
 
-        let (mut curr_visited, curr_pos) = queue.pop_front().unwrap();
+impl TrailGraph {
+    pub const START:usize = 0;
+    pub const END:usize = 1;
 
-        if curr_pos == end {
-            // Found end
-            if curr_visited.len() > max_len {
-                max_len = curr_visited.len();
-            }
-            continue;
-        } else if curr_visited.contains(&curr_pos){
-            continue;
-        }
+    /// Compresses the map into its junctions, following each trail that leaves one to the next
+    /// junction. Trails that end in a dead end or, unless `part2`, climb a slope are left out.
+    pub fn new(map:&Grid<char>, part2:bool) -> TrailGraph {
+        let start = (1, 0);
+        let end = (map.width() - 2, map.height() - 1);
+        let mut junctions = vec![start, end];
+        junctions.extend(map.positions()
+            .filter(|pos| map[*pos] != '#' && *pos != start && *pos != end)
+            .filter(|pos| map.neighbors4(*pos).filter(|next| map[*next] != '#').count() > 2));
 
-        curr_visited.insert(curr_pos);
+        let trails = junctions.iter()
+            .map(|junction| Direction::ALL.iter()
+                .filter_map(|direction| follow_trail(map, &junctions, *junction, *direction, part2))
+                .collect())
+            .collect();
+        TrailGraph{junctions, trails}
+    }
 
-        let directions = [Direction::Left, Direction::Right, Direction::Up, Direction::Down];
+    /// Steps of the longest hike from the start to the end that doesn't visit a junction twice, trying
+    /// every hike with the junctions visited so far kept as bits
+    pub fn longest_hike(&self) -> Option<usize> {
+        if self.junctions.len() > 64 {
+            return None;
+        }
 
-        let mut next_positions = directions.iter()
-            .copied()
-            .filter(|dir| dir.is_next_valid(curr_pos, &map, part2))
-            .map(|dir| dir.get_next_pos(curr_pos))
-            .filter(|next_pos| !curr_visited.contains(next_pos))
-            .collect::<Vec<(i32, i32)>>();
+        // The end is at the edge of the map, so a hike passing the only junction next to it would
+        // never make it there afterwards and has to head straight for the end
+        let into_end = self.trails.iter()
+            .enumerate()
+            .filter_map(|(junction, trails)| trails.iter()
+                .find(|(next, _)| *next == TrailGraph::END)
+                .map(|(_, length)| (junction, *length)))
+            .collect::<Vec<(usize, usize)>>();
+        let last = if into_end.len() == 1 { Some(into_end[0]) } else { None };
 
+        self.longest_from(TrailGraph::START, 1 << TrailGraph::START, last)
+    }
 
-        if next_positions.len() == 1 {
-            queue.push_front( (curr_visited, next_positions.remove(0)));
-        } else {
-            // Take first
-            while !next_positions.is_empty() {
-                queue.push_front( (curr_visited.clone(), next_positions.remove(0)));
-            }
+    fn longest_from(&self, junction:usize, visited:u64, last:Option<(usize, usize)>) -> Option<usize> {
+        if junction == TrailGraph::END {
+            return Some(0);
         }
+        if let Some((_, length)) = last.filter(|(last, _)| *last == junction) {
+            return Some(length);
+        }
+
+        self.trails[junction].iter()
+            .filter(|(next, _)| visited & (1 << next) == 0)
+            .filter_map(|(next, length)| self.longest_from(*next, visited | (1 << next), last)
+                .map(|rest| length + rest))
+            .max()
+    }
+}
+
+/// One line per trail, e.g. `(1, 0) -> (3, 5): 15 steps`
+impl fmt::Display for TrailGraph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines = self.trails.iter()
+            .enumerate()
+            .flat_map(|(junction, trails)| trails.iter()
+                .map(move |(next, length)| format!("{:?} -> {:?}: {} steps", self.junctions[junction], self.junctions[*next], length)))
+            .collect::<Vec<String>>();
+        write!(f, "{}", lines.join("\n"))
     }
+}
 
-    max_len
+// Walks the trail leaving `junction` in `direction` up to the next junction, that junction's index
+// and the steps it took
+fn follow_trail(map:&Grid<char>, junctions:&[Pos], junction:Pos, direction:Direction, part2:bool) -> Option<(usize, usize)> {
+    let (mut previous, mut pos) = (junction, next_valid(map, junction, direction, part2)?);
+    let mut steps = 1;
+    loop {
+        if let Some(index) = junctions.iter().position(|other| *other == pos) {
+            return Some((index, steps));
+        }
+        // Between junctions there is only one way forward
+        let next = Direction::ALL.iter()
+            .filter_map(|direction| next_valid(map, pos, *direction, part2))
+            .find(|next| *next != previous)?;
+        (previous, pos) = (pos, next);
+        steps += 1;
+    }
 }
 
-fn part1(lines:Vec<&str>) -> String {
-    let map = parse(&lines);
-    dfs(&map, false).to_string()
+fn longest_hike(map:&Grid<char>, part2:bool) -> Result<usize> {
+    let graph = TrailGraph::new(map, part2);
+    if graph.junctions.len() > 64 {
+        return Err(SolveError::unsolvable("more than 64 junctions to keep track of"));
+    }
+    graph.longest_hike()
+        .ok_or_else(|| SolveError::unsolvable("no hike reaches the bottom row"))
 }
 
-fn part2(lines:Vec<&str>) -> String {
-    let map = parse(&lines);
-    dfs(&map, true).to_string()
+fn part1(map:&Grid<char>) -> Result<String> {
+    Ok(longest_hike(map, false)?.to_string())
 }
 
+fn part2(map:&Grid<char>) -> Result<String> {
+    Ok(longest_hike(map, true)?.to_string())
+}
 
-#[cfg(test)]
-mod tests {
-    use std::assert_eq;
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-    use util::Part::{Part1, Part2};
 
-    const INPUT:&str = "#.#####################
+const EXAMPLE:&str = "#.#####################
 #.......#########...###
 #######.#########.#.###
 ###.....#.>.>.###.#.###
@@ -153,25 +205,58 @@ mod tests {
 #.....###...###...#...#
 #####################.#";
 
+const EXAMPLES:[Example;2] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "94"},
+    Example{part: Part::Part2, input: EXAMPLE, expected: "154"},
+];
+
+
+#[cfg(test)]
+mod tests {
+    use std::assert_eq;
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
+
+
     #[test]
     fn test1() {
-        assert_eq!("94", solve(INPUT.to_string(), Part1));
+        assert_eq!("94", solve(EXAMPLE.to_string(), Part1).unwrap());
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_23.txt");
-        assert_eq!("2174", solve(input.to_string(), Part1));
+        assert_answer(23, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("154", solve(INPUT.to_string(), Part2));
+        assert_eq!("154", solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
-        let _input = include_str!("../../input/input_23.txt");
-        //assert_eq!("2", solve(input.to_string(), Part2));
+        let input = include_str!("../../input/input_23.txt");
+        assert_answer(23, Part2, solve(input.to_string(), Part2));
+    }
+
+    #[test]
+    fn test_trail_graph() {
+        let map = parse(EXAMPLE).unwrap();
+        let graph = TrailGraph::new(&map, false);
+        assert_eq!(9, graph.junctions.len());
+        assert_eq!(((1, 0), (21, 22)), (graph.junctions[TrailGraph::START], graph.junctions[TrailGraph::END]));
+        // Slopes only lead away from the start
+        let first = graph.junctions.iter().position(|pos| *pos == (3, 5)).unwrap();
+        assert_eq!(vec![(first, 15)], graph.trails[TrailGraph::START]);
+        assert!(graph.trails[first].iter().all(|(next, _)| *next != TrailGraph::START));
+        assert!(graph.trails[TrailGraph::END].is_empty());
+        assert_eq!("(1, 0) -> (3, 5): 15 steps", graph.to_string().lines().next().unwrap());
+
+        let graph = TrailGraph::new(&map, true);
+        assert!(graph.trails[first].contains(&(TrailGraph::START, 15)));
+        assert_eq!(Some(154), graph.longest_hike());
     }
 }
@@ -1,104 +1,253 @@
-use super::Part;
+use std::convert::TryFrom;
 
+use super::rational::Rational;
+use super::{parse_token, Part, Puzzle, Result, SolveError, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
 
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Vec<HailStone>;
+
+    fn day(&self) -> u8 {
+        24
+    }
+
+    fn title(&self) -> &'static str {
+        "Never Tell Me The Odds"
+    }
+
+    fn parse(&self, input:&str) -> Result<Vec<HailStone>> {
+        parse(input)
+    }
+
+    fn part1(&self, hailstones:&Vec<HailStone>) -> Result<String> {
+        part1(hailstones)
+    }
+
+    fn part2(&self, hailstones:&Vec<HailStone>) -> Result<String> {
+        part2(hailstones)
     }
 }
 
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
 
-#[derive(Debug)]
-struct HailStone {
-    x:i64,
-    y:i64,
-    z:i64,
-    kx:i64,
-    ky:i64,
-    kz:i64,
+
+/// Position and velocity of a hailstone, or of the rock thrown at them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HailStone {
+    pub x:i64,
+    pub y:i64,
+    pub z:i64,
+    pub kx:i64,
+    pub ky:i64,
+    pub kz:i64,
 }
 
+type Vector = [i128;3];
+
 impl HailStone {
-    fn from_str(line:&str) -> HailStone {
-        let mut it = line.split(|c| c == ' ' || c == ',' || c == '@')
+    fn from_str(index:usize, line:&str) -> Result<HailStone> {
+        let values = line.split([' ', ',', '@'])
             .filter(|s| !s.is_empty())
-            .map(|s| s.parse::<i64>().unwrap());
-        HailStone { x: it.next().unwrap(), y: it.next().unwrap(), z: it.next().unwrap(), kx: it.next().unwrap(), ky: it.next().unwrap(), kz: it.next().unwrap() }
+            .map(|s| parse_token::<i64>(index, line, s))
+            .collect::<Result<Vec<i64>>>()?;
+        if values.len() != 6 {
+            return Err(SolveError::malformed(index + 1, 1, "expected 'px, py, pz @ vx, vy, vz'"));
+        }
+        Ok(HailStone { x: values[0], y: values[1], z: values[2], kx: values[3], ky: values[4], kz: values[5] })
     }
 
-    fn get_x(&self, w:f64) -> f64 {
-        self.x as f64 + self.kx as f64 * w
+    fn position(&self) -> Vector {
+        [self.x as i128, self.y as i128, self.z as i128]
     }
 
-    fn get_y(&self, w:f64) -> f64 {
-        self.y as f64 + self.ky as f64 * w
+    fn velocity(&self) -> Vector {
+        [self.kx as i128, self.ky as i128, self.kz as i128]
     }
 
-    fn calc_w_and_t(&self, other:&HailStone) -> (f64,f64) {
-        let kx1 = self.kx;
-        let ky1 = self.ky;
-        let kx2 = other.kx;
-        let ky2 = other.ky;
-        let x1 = self.x;
-        let x2 = other.x;
-        let y1 = self.y;
-        let y2 = other.y;
-        let k = kx2 as f64 / kx1 as f64;
-        let m = (x2 - x1) as f64 / kx1 as f64;
-        //let w = ((y2 - y1) as f64 - ky1 as f64 * m) / (ky1 as f64 * k - ky2 as f64);
-        let numerator = (y2 - y1) as f64 - ky1 as f64 * m;
-        let denominator = ky1 as f64 * k - ky2 as f64;
-        let w = numerator / denominator;
-        let t = k * w+ m;
-        return (w, t)
+    /// Time at which `rock` hits this hailstone, if it ever does
+    fn hit_by(&self, rock:&HailStone) -> Result<Option<i128>> {
+        let offset = sub(rock.position(), self.position())?;
+        let closing = sub(self.velocity(), rock.velocity())?;
+        // Moving at the same speed along an axis they only meet if they already agree on it
+        let time = match (0..3).find(|axis| closing[*axis] != 0) {
+            None => 0,
+            Some(axis) if offset[axis].checked_rem(closing[axis]).ok_or_else(too_large)? == 0 => {
+                offset[axis].checked_div(closing[axis]).ok_or_else(too_large)?
+            },
+            Some(_) => return Ok(None),
+        };
+        // A product too large for an i128 can't equal the offset
+        Ok(Some(time).filter(|time| *time >= 0 && (0..3).all(|axis| time.checked_mul(closing[axis]) == Some(offset[axis]))))
     }
 
-    fn intersects_at_plane(&self, other:&HailStone) -> Option<(f64, f64)> {
-        if self.kx == other.kx && self.ky == other.ky {
-            None
-        } else {
-            let (w, t) = self.calc_w_and_t(other);
-            //println!("w = {}, t = {}", w, t);
-            if w.is_sign_positive() && w.is_finite() && t.is_sign_positive() && t.is_finite() {
-                Some((other.get_x(w), other.get_y(w)))
-            } else {
-                None
-            }
+    fn get_x(&self, w:Rational) -> Result<Rational> {
+        Rational::from(self.kx).checked_mul(w).and_then(|moved| moved.checked_add(Rational::from(self.x))).ok_or_else(too_large)
+    }
+
+    fn get_y(&self, w:Rational) -> Result<Rational> {
+        Rational::from(self.ky).checked_mul(w).and_then(|moved| moved.checked_add(Rational::from(self.y))).ok_or_else(too_large)
+    }
+
+    // Times at which the paths of both hailstones in the x-y plane pass their crossing, `w` for the other
+    // hailstone and `t` for this one, if the paths aren't parallel
+    fn calc_w_and_t(&self, other:&HailStone) -> Result<Option<(Rational, Rational)>> {
+        let (kx1, ky1, kx2, ky2) = (self.kx as i128, self.ky as i128, other.kx as i128, other.ky as i128);
+        let dx = other.x as i128 - self.x as i128;
+        let dy = other.y as i128 - self.y as i128;
+        let determinant = |a:i128, b:i128, c:i128, d:i128| a.checked_mul(d)
+            .zip(b.checked_mul(c))
+            .and_then(|(ad, bc)| ad.checked_sub(bc))
+            .ok_or_else(too_large);
+        let denominator = determinant(kx1, kx2, ky1, ky2)?;
+        if denominator == 0 {
+            return Ok(None);
         }
+        let w = Rational::new(determinant(dx, dy, kx1, ky1)?, denominator);
+        let t = Rational::new(determinant(dx, dy, kx2, ky2)?, denominator);
+        Ok(Some((w, t)))
     }
 
-    fn intersects_at_plane_within(&self, other:&HailStone, min_value:f64, max_value:f64) -> bool {
-        if let Some((x,y)) = self.intersects_at_plane(other) {
-            let inside = x >= min_value && x <= max_value && y >= min_value && y <= max_value;
-            inside
+    /// Where the paths of both hailstones cross in the x-y plane, if both get there in the future. Fails
+    /// when the crossing is too far out to work out exactly.
+    pub fn intersects_at_plane(&self, other:&HailStone) -> Result<Option<(Rational, Rational)>> {
+        match self.calc_w_and_t(other)? {
+            Some((w, t)) if !w.is_negative() && !t.is_negative() => Ok(Some((other.get_x(w)?, other.get_y(w)?))),
+            _ => Ok(None),
+        }
+    }
+
+    fn intersects_at_plane_within(&self, other:&HailStone, min_value:i64, max_value:i64) -> Result<bool> {
+        let (min_value, max_value) = (Rational::from(min_value), Rational::from(max_value));
+        if let Some((x,y)) = self.intersects_at_plane(other)? {
+            Ok(x >= min_value && x <= max_value && y >= min_value && y <= max_value)
         } else {
-            false
+            Ok(false)
         }
     }
 }
 
-fn intersects(lines:Vec<&str>, min_value:f64, max_value:f64) -> u32 {
-    let hailstones:Vec<HailStone> = lines.iter().map(|line|HailStone::from_str(line)).collect();
+// Hailstones too far apart or too fast to follow exactly in `i128`
+fn too_large() -> SolveError {
+    SolveError::unsolvable("hailstone arithmetic overflowed i128")
+}
+
+pub fn parse(input:&str) -> Result<Vec<HailStone>> {
+    input.lines()
+        .enumerate()
+        .map(|(index, line)| HailStone::from_str(index, line))
+        .collect()
+}
+
+// The vector with `component` of each axis, an error if one of them overflows
+fn vector(component:impl Fn(usize) -> Option<i128>) -> Result<Vector> {
+    Ok([component(0).ok_or_else(too_large)?, component(1).ok_or_else(too_large)?, component(2).ok_or_else(too_large)?])
+}
+
+fn sub(a:Vector, b:Vector) -> Result<Vector> {
+    vector(|axis| a[axis].checked_sub(b[axis]))
+}
+
+fn cross(a:Vector, b:Vector) -> Result<Vector> {
+    vector(|axis| {
+        let (next, last) = ((axis + 1) % 3, (axis + 2) % 3);
+        a[next].checked_mul(b[last])?.checked_sub(a[last].checked_mul(b[next])?)
+    })
+}
+
+fn dot(a:Vector, b:Vector) -> Result<i128> {
+    (0..3).try_fold(0i128, |sum, axis| a[axis].checked_mul(b[axis])?.checked_add(sum)).ok_or_else(too_large)
+}
+
+/// Number of pairs of hailstones whose paths cross, looking only at x and y, inside the square from
+/// `min_value` to `max_value`
+pub fn intersects(hailstones:&[HailStone], min_value:i64, max_value:i64) -> Result<u32> {
     let mut intersections_within_interval = 0;
     for i in 0..hailstones.len() {
         for j in i+1..hailstones.len() {
-            if i != j && hailstones[i].intersects_at_plane_within(&hailstones[j], min_value, max_value) {
+            if i != j && hailstones[i].intersects_at_plane_within(&hailstones[j], min_value, max_value)? {
                 //println!("{} intersects with {}", i, j);
                 intersections_within_interval += 1;
             }
         }
     }
-    intersections_within_interval
+    Ok(intersections_within_interval)
+}
+fn part1(hailstones:&[HailStone]) -> Result<String> {
+    Ok(intersects(hailstones, 200000000000000, 400000000000000)?.to_string())
 }
-fn part1(lines:Vec<&str>) -> String {
-    intersects(lines, 200000000000000.0, 400000000000000.0).to_string()
+
+/// Position and velocity of the rock that, thrown at time zero, hits every hailstone at a whole
+/// nanosecond.
+///
+/// Seen from the first hailstone the rock passes through the origin, so it moves in the plane through
+/// the origin and the path of a second hailstone. Two more hailstones crossing that plane give two points
+/// on the rock's path, and the times they are there, which fixes the throw. It is all done in integers,
+/// a division that doesn't come out even means no such throw exists.
+pub fn throw_rock(hailstones:&[HailStone]) -> Result<HailStone> {
+    let no_throw = || SolveError::unsolvable("no single throw hits every hailstone");
+    let first = hailstones.first().ok_or_else(no_throw)?;
+    let relative = hailstones[1..].iter()
+        .map(|hailstone| Ok((sub(hailstone.position(), first.position())?, sub(hailstone.velocity(), first.velocity())?)))
+        .collect::<Result<Vec<(Vector, Vector)>>>()?;
+
+    let normal = relative.iter()
+        .map(|(position, velocity)| cross(*position, *velocity))
+        .find(|normal| normal.as_ref().map_or(true, |normal| *normal != [0, 0, 0]))
+        .ok_or_else(no_throw)??;
+    let mut crossings = relative.iter()
+        .map(|(position, velocity)| {
+            let speed = dot(*velocity, normal)?;
+            if speed == 0 {
+                return Ok(None);
+            }
+            let distance = dot(*position, normal)?.checked_neg().ok_or_else(too_large)?;
+            if distance % speed != 0 {
+                return Err(no_throw());
+            }
+            let time = distance / speed;
+            Ok(Some((time, vector(|axis| time.checked_mul(velocity[axis])?.checked_add(position[axis]))?)))
+        })
+        .filter_map(Result::transpose);
+    let (time1, point1) = crossings.next().ok_or_else(no_throw)??;
+    let (time2, point2) = crossings
+        .find(|crossing| crossing.as_ref().map_or(true, |(time, _)| *time != time1))
+        .ok_or_else(no_throw)??;
+
+    let travelled = sub(point2, point1)?;
+    let elapsed = time2.checked_sub(time1).ok_or_else(too_large)?;
+    if vector(|axis| travelled[axis].checked_rem(elapsed))? != [0, 0, 0] {
+        return Err(no_throw());
+    }
+    let velocity = vector(|axis| travelled[axis].checked_div(elapsed))?;
+    let position = vector(|axis| point1[axis].checked_sub(time1.checked_mul(velocity[axis])?))?;
+
+    // Relative to the first hailstone, anything that doesn't fit an i64 isn't a throw
+    let to_i64 = |value:i128, offset:i64| value.checked_add(offset as i128)
+        .and_then(|value| i64::try_from(value).ok())
+        .ok_or_else(no_throw);
+    let rock = HailStone {
+        x: to_i64(position[0], first.x)?,
+        y: to_i64(position[1], first.y)?,
+        z: to_i64(position[2], first.z)?,
+        kx: to_i64(velocity[0], first.kx)?,
+        ky: to_i64(velocity[1], first.ky)?,
+        kz: to_i64(velocity[2], first.kz)?,
+    };
+    for hailstone in hailstones {
+        if hailstone.hit_by(&rock)?.is_none() {
+            return Err(no_throw());
+        }
+    }
+    Ok(rock)
 }
 
-fn part2(_lines:Vec<&str>) -> String {
-    "2".to_string()
+fn part2(hailstones:&[HailStone]) -> Result<String> {
+    let rock = throw_rock(hailstones)?;
+    Ok((rock.x + rock.y + rock.z).to_string())
 }
 
 
@@ -108,6 +257,7 @@ mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     use util::Part::{Part1, Part2};
+    use util::answers::assert_answer;
 
     const INPUT:&str = "19, 13, 30 @ -2,  1, -2
 18, 19, 22 @ -1, -1, -2
@@ -117,24 +267,61 @@ mod tests {
 
     #[test]
     fn test1() {
-        assert_eq!("2",     intersects(INPUT.lines().collect(), 7.0, 27.0).to_string()
+        assert_eq!("2",     intersects(&parse(INPUT).unwrap(), 7, 27).unwrap().to_string()
         );
     }
 
+    #[test]
+    fn test_intersects_at_plane() {
+        let hailstones = parse(INPUT).unwrap();
+        let crossing = (Rational::new(43, 3), Rational::new(46, 3));
+        assert_eq!(Ok(Some(crossing)), hailstones[0].intersects_at_plane(&hailstones[1]));
+        assert_eq!(Ok(None), hailstones[0].intersects_at_plane(&hailstones[4]));
+
+        // Crossings a millionth beyond the bound, which f64 would round onto it
+        let beyond = parse("400000000000000, 0, 0 @ 1, 1000000, 0\n0, 1, 0 @ 1, 0, 0").unwrap();
+        assert_eq!(Ok(0), intersects(&beyond, 0, 400000000000000));
+        let within = parse("399999999999999, 0, 0 @ 1, 1000000, 0\n0, 1, 0 @ 1, 0, 0").unwrap();
+        assert_eq!(Ok(1), intersects(&within, 0, 400000000000000));
+        let on_bound = parse("0, 7, 0 @ 1, 0, 0\n7, 0, 0 @ 0, 1, 0").unwrap();
+        assert_eq!((Ok(1), Ok(0)), (intersects(&on_bound, 7, 27), intersects(&on_bound, 8, 27)));
+
+        // Starting further apart than an i64 can hold
+        let apart = parse("9223372036854775807, 0, 0 @ -1, 1, 0\n-9223372036854775808, 0, 0 @ 1, 1, 0").unwrap();
+        assert_eq!(Ok(Some((Rational::new(-1, 2), Rational::new(18446744073709551615, 2)))), apart[0].intersects_at_plane(&apart[1]));
+
+        // Too far apart and too fast to find the crossing in an i128
+        let fast = parse("9223372036854775807, 9223372036854775807, 0 @ -9223372036854775808, 9223372036854775807, 0
+-9223372036854775808, -9223372036854775808, 0 @ 1, 0, 0").unwrap();
+        assert_eq!(Err(too_large()), intersects(&fast, 0, 1));
+    }
+
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_24.txt");
-        assert_eq!("27328", solve(input.to_string(), Part1));
+        assert_answer(24, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("2", solve(INPUT.to_string(), Part2));
+        assert_eq!("47", solve(INPUT.to_string(), Part2).unwrap());
+        let rock = HailStone { x: 24, y: 13, z: 10, kx: -3, ky: 1, kz: 2 };
+        assert_eq!(Ok(rock.clone()), throw_rock(&parse(INPUT).unwrap()));
+        assert_eq!(Ok(Some(5)), parse(INPUT).unwrap()[0].hit_by(&rock));
+
+        let missed = INPUT.replace("20, 19, 15", "20, 19, 16");
+        assert_eq!(Err(SolveError::unsolvable("no single throw hits every hailstone")), throw_rock(&parse(&missed).unwrap()));
+
+        // Valid hailstones so far apart and fast that the plane of the throw doesn't fit an i128
+        let fast = parse("9223372036854775807, 0, 0 @ -9223372036854775808, 0, 0
+-9223372036854775808, 0, 9223372036854775807 @ 9223372036854775807, 9223372036854775807, 0
+0, 0, 0 @ 1, 1, 1").unwrap();
+        assert_eq!(Err(too_large()), throw_rock(&fast));
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_24.txt");
-        assert_eq!("2", solve(input.to_string(), Part2));
+        assert_answer(24, Part2, solve(input.to_string(), Part2));
     }
 }
@@ -1,51 +1,235 @@
-use super::Part;
+use std::collections::{HashMap, VecDeque};
 
+use super::{Example, Part, Puzzle, Result, SolveError, Solver};
 
-pub fn solve(input : String, part: Part) -> String {
 
-    let lines = input.lines().collect();
-    match part {
-        Part::Part1 => part1(lines),
-        Part::Part2 => part2(lines)
+pub struct Solution;
+
+impl Puzzle for Solution {
+    type Input = Wiring;
+
+    fn day(&self) -> u8 {
+        25
+    }
+
+    fn title(&self) -> &'static str {
+        "Snowverload"
+    }
+
+    fn parse(&self, input:&str) -> Result<Wiring> {
+        parse(input)
     }
+
+    fn part1(&self, wiring:&Wiring) -> Result<String> {
+        part1(wiring)
+    }
+
+    fn part2(&self, wiring:&Wiring) -> Result<String> {
+        part2(wiring)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        &EXAMPLES
+    }
+}
+
+pub fn solve(input : String, part: Part) -> Result<String> {
+    Solution.solve(part, &input)
+}
+
+/// Answer of part 2, the last day only has one puzzle and its second star comes with the other 49
+pub const NO_PART_2:&str = "no part 2";
+
+
+/// Components and the wires between them, components are numbered in the order they first appear
+#[derive(Debug, PartialEq, Eq)]
+pub struct Wiring {
+    pub names:Vec<String>,
+    /// The components each component is wired to, by number
+    pub connections:Vec<Vec<usize>>,
 }
 
-fn part1(_lines:Vec<&str>) -> String {
-    "1".to_string()
+/// Wires whose removal splits the components into two groups, and the sizes of those groups
+#[derive(Debug, PartialEq, Eq)]
+pub struct Cut {
+    /// Both ends of each wire by name, in alphabetical order
+    pub wires:Vec<(String, String)>,
+    pub group_sizes:(usize, usize),
 }
 
-fn part2(_lines:Vec<&str>) -> String {
-    "2".to_string()
+pub fn parse(input:&str) -> Result<Wiring> {
+    let mut wiring = Wiring{names: vec![], connections: vec![]};
+    let mut numbers = HashMap::new();
+    let mut number = |wiring:&mut Wiring, name:&str| *numbers.entry(name.to_string()).or_insert_with(|| {
+        wiring.names.push(name.to_string());
+        wiring.connections.push(vec![]);
+        wiring.names.len() - 1
+    });
+
+    for (index, line) in input.lines().enumerate() {
+        let (name, others) = line.split_once(':').ok_or_else(|| SolveError::missing(index, line, "':'"))?;
+        if name.trim().is_empty() {
+            return Err(SolveError::malformed(index + 1, 1, "missing component"));
+        }
+        let component = number(&mut wiring, name.trim());
+        for other in others.split_whitespace() {
+            let other = number(&mut wiring, other);
+            wiring.connections[component].push(other);
+            wiring.connections[other].push(component);
+        }
+    }
+    Ok(wiring)
 }
 
+/// Finds at most `max_wires` wires that split the components into two groups, if there are such wires.
+///
+/// The wires are a minimum cut between the first component and some other. For each other component in
+/// turn, up to `max_wires` + 1 paths that share no wire are sent from the first. When no more than
+/// `max_wires` fit, the wires leaving the components still reachable are the cut. Components wired
+/// together more than once take a path along each of those wires.
+pub fn cut_wires(wiring:&Wiring, max_wires:usize) -> Option<Cut> {
+    let mut capacity:HashMap<(usize, usize), i32> = HashMap::new();
+    for (component, others) in wiring.connections.iter().enumerate() {
+        for other in others.iter() {
+            *capacity.entry((component, *other)).or_default() += 1;
+        }
+    }
+
+    let source = 0;
+    for sink in 1..wiring.names.len() {
+        // Flow between each pair of components in each direction, a wire carries at most one path
+        let mut flow:HashMap<(usize, usize), i32> = HashMap::new();
+        let mut paths = 0;
+        let reachable = loop {
+            let previous = augmenting_path(wiring, &capacity, &flow, source);
+            if previous[sink].is_none() || paths > max_wires {
+                break previous;
+            }
+            let mut component = sink;
+            while let Some(from) = previous[component].filter(|_| component != source) {
+                *flow.entry((from, component)).or_default() += 1;
+                *flow.entry((component, from)).or_default() -= 1;
+                component = from;
+            }
+            paths += 1;
+        };
+        if paths > max_wires {
+            continue;
+        }
+
+        let mut wires = (0..wiring.names.len())
+            .filter(|component| reachable[*component].is_some())
+            .flat_map(|component| wiring.connections[component].iter()
+                .filter(|other| reachable[**other].is_none())
+                .map(move |other| (component, *other)))
+            .map(|(a, b)| {
+                let (a, b) = (wiring.names[a].clone(), wiring.names[b].clone());
+                if a < b { (a, b) } else { (b, a) }
+            })
+            .collect::<Vec<(String, String)>>();
+        wires.sort();
+        let group = reachable.iter().filter(|previous| previous.is_some()).count();
+        return Some(Cut{wires, group_sizes: (group, wiring.names.len() - group)});
+    }
+    None
+}
+
+// Breadth first search over wires with capacity left, the component each component was reached from,
+// the source is reached from itself
+fn augmenting_path(wiring:&Wiring, capacity:&HashMap<(usize, usize), i32>, flow:&HashMap<(usize, usize), i32>, source:usize) -> Vec<Option<usize>> {
+    let mut previous = vec![None; wiring.names.len()];
+    previous[source] = Some(source);
+    let mut queue = VecDeque::from([source]);
+    while let Some(component) = queue.pop_front() {
+        for other in wiring.connections[component].iter().copied() {
+            if previous[other].is_none() && flow.get(&(component, other)).copied().unwrap_or(0) < capacity[&(component, other)] {
+                previous[other] = Some(component);
+                queue.push_back(other);
+            }
+        }
+    }
+    previous
+}
+
+fn part1(wiring:&Wiring) -> Result<String> {
+    let cut = cut_wires(wiring, 3)
+        .ok_or_else(|| SolveError::unsolvable("no three wires split the components in two"))?;
+    Ok((cut.group_sizes.0 * cut.group_sizes.1).to_string())
+}
+
+fn part2(_wiring:&Wiring) -> Result<String> {
+    Ok(NO_PART_2.to_string())
+}
+
+
+const EXAMPLE:&str = "jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr";
+
+const EXAMPLES:[Example;1] = [
+    Example{part: Part::Part1, input: EXAMPLE, expected: "54"},
+];
+
 
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     use util::Part::{Part1, Part2};
-
-    const INPUT:&str = "";
+    use util::answers::assert_answer;
 
     #[test]
     fn test1() {
-        assert_eq!("1", solve(INPUT.to_string(), Part1));
+        assert_eq!("54", solve(EXAMPLE.to_string(), Part1).unwrap());
+    }
+
+    #[test]
+    fn test_cut_wires() {
+        let wiring = parse(EXAMPLE).unwrap();
+        assert_eq!(15, wiring.names.len());
+        let wires = [("bvb", "cmg"), ("hfx", "pzl"), ("jqt", "nvd")].iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect::<Vec<(String, String)>>();
+        let cut = cut_wires(&wiring, 3).unwrap();
+        assert_eq!(wires, cut.wires);
+        assert_eq!(54, cut.group_sizes.0 * cut.group_sizes.1);
+        assert_eq!(None, cut_wires(&wiring, 2));
+
+        // Two wires between a and b, one between b and c
+        let doubled = parse("a: b\nb: a c").unwrap();
+        let cut = |wires:&[(&str, &str)], group_sizes| Some(Cut{
+            wires: wires.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect(),
+            group_sizes,
+        });
+        assert_eq!(cut(&[("b", "c")], (2, 1)), cut_wires(&doubled, 1));
+        assert_eq!(cut(&[("a", "b"), ("a", "b")], (1, 2)), cut_wires(&doubled, 2));
+        assert_eq!(Err(SolveError::malformed(1, 4, "missing ':'")), parse("jqt"));
     }
 
     #[test]
     fn test_part1() {
         let input = include_str!("../../input/input_25.txt");
-        assert_eq!("1", solve(input.to_string(), Part1));
+        assert_answer(25, Part1, solve(input.to_string(), Part1));
     }
 
     #[test]
     fn test2() {
-        assert_eq!("2", solve(INPUT.to_string(), Part2));
+        assert_eq!(NO_PART_2, solve(EXAMPLE.to_string(), Part2).unwrap());
     }
 
     #[test]
     fn test_part2() {
         let input = include_str!("../../input/input_25.txt");
-        assert_eq!("2", solve(input.to_string(), Part2));
+        assert_answer(25, Part2, solve(input.to_string(), Part2));
     }
 }
@@ -0,0 +1,98 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+pub type Result<T> = std::result::Result<T, SolveError>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    UnknownDay(u8),
+    UnknownPart(u8),
+    /// Line and column are one based
+    MalformedInput { line: usize, column: usize, message: String },
+    Unsolvable(String),
+    /// The puzzle input could not be read
+    MissingInput(String),
+}
+
+impl SolveError {
+    pub fn malformed<S: Into<String>>(line: usize, column: usize, message: S) -> SolveError {
+        SolveError::MalformedInput { line, column, message: message.into() }
+    }
+
+    pub fn unsolvable<S: Into<String>>(message: S) -> SolveError {
+        SolveError::Unsolvable(message.into())
+    }
+
+    /// Error located at `token`, a slice of `line` which is line number `index` (zero based) of the input
+    pub fn at_token<S: Into<String>>(index: usize, line: &str, token: &str, message: S) -> SolveError {
+        SolveError::malformed(index + 1, column_of(line, token), message)
+    }
+
+    /// Error for something missing at the end of line number `index` (zero based)
+    pub fn missing<S: Into<String>>(index: usize, line: &str, what: S) -> SolveError {
+        SolveError::malformed(index + 1, line.chars().count() + 1, format!("missing {}", what.into()))
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::UnknownDay(day) => write!(f, "unknown day {}", day),
+            SolveError::UnknownPart(part) => write!(f, "unknown part {}", part),
+            SolveError::MalformedInput { line, column, message } =>
+                write!(f, "malformed input at line {}, column {}: {}", line, column, message),
+            SolveError::Unsolvable(message) => write!(f, "puzzle has no solution: {}", message),
+            SolveError::MissingInput(message) => write!(f, "cannot read input {}", message),
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+// One based column of `token` within `line`, falls back to the first column if it is not a slice of it
+fn column_of(line: &str, token: &str) -> usize {
+    let offset = (token.as_ptr() as usize).wrapping_sub(line.as_ptr() as usize);
+    if offset <= line.len() && line.is_char_boundary(offset) {
+        line[..offset].chars().count() + 1
+    } else {
+        1
+    }
+}
+
+/// Parses `token`, a slice of `line` which is line number `index` (zero based) of the input
+pub fn parse_token<T: FromStr>(index: usize, line: &str, token: &str) -> Result<T> {
+    token.parse::<T>()
+        .map_err(|_| SolveError::at_token(index, line, token, format!("unexpected '{}'", token)))
+}
+
+/// Next token from `it`, reported as missing `what` at the end of `line`
+pub fn next_token<'a, I>(it: &mut I, index: usize, line: &str, what: &str) -> Result<&'a str>
+    where
+        I: Iterator<Item=&'a str>,
+{
+    it.next().ok_or_else(|| SolveError::missing(index, line, what))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_token() {
+        let line = "Time: 7 x5";
+        let token = &line[8..];
+        assert_eq!(Ok(7), parse_token::<u32>(0, line, &line[6..7]));
+        assert_eq!(Err(SolveError::malformed(3, 9, "unexpected 'x5'")), parse_token::<u32>(2, line, token));
+    }
+
+    #[test]
+    fn test_next_token() {
+        let line = "a b";
+        let mut it = line.split(' ');
+        assert_eq!(Ok("a"), next_token(&mut it, 0, line, "name"));
+        assert_eq!(Ok("b"), next_token(&mut it, 0, line, "name"));
+        assert_eq!(Err(SolveError::malformed(1, 4, "missing name")), next_token(&mut it, 0, line, "name"));
+    }
+}
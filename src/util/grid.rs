@@ -0,0 +1,325 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use super::{Result, SolveError};
+
+/// Position of a cell as (x, y), y grows downwards
+pub type Pos = (usize, usize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// Clockwise, starting upwards
+    pub const ALL:[Direction;4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+        }
+    }
+
+    pub fn opposite(self) -> Direction {
+        self.turn_right().turn_right()
+    }
+
+    pub fn turn_right(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn turn_left(self) -> Direction {
+        self.turn_right().opposite()
+    }
+
+    pub fn is_vertical(self) -> bool {
+        self == Direction::Up || self == Direction::Down
+    }
+
+    pub fn is_horizontal(self) -> bool {
+        !self.is_vertical()
+    }
+}
+
+/// Rectangular grid of cells, stored row by row
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width:usize,
+    height:usize,
+    cells:Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width:usize, height:usize, cells:Vec<T>) -> Grid<T> {
+        assert_eq!(width * height, cells.len(), "{}x{} grid", width, height);
+        Grid{width, height, cells}
+    }
+
+    pub fn filled(width:usize, height:usize, value:T) -> Grid<T> where T:Clone {
+        Grid::new(width, height, vec![value; width * height])
+    }
+
+    /// Parses one row per line, `cell` maps each character and `expected` describes the
+    /// characters it accepts in the error for any other character
+    pub fn parse(input:&str, cell:impl Fn(char) -> Option<T>, expected:&str) -> Result<Grid<T>> {
+        Grid::parse_at(0, input, cell, expected)
+    }
+
+    /// Like `parse`, for a grid that starts at line `first_index` (zero-based) of the input
+    pub fn parse_at(first_index:usize, input:&str, cell:impl Fn(char) -> Option<T>, expected:&str) -> Result<Grid<T>> {
+        let width = input.lines().next().map_or(0, |line| line.chars().count());
+        if width == 0 {
+            return Err(SolveError::malformed(first_index + 1, 1, "empty grid"));
+        }
+
+        let mut cells = vec![];
+        let mut height = 0;
+        for (y, line) in input.lines().enumerate() {
+            if line.chars().count() != width {
+                return Err(SolveError::malformed(first_index + y + 1, 1, format!("expected {} columns", width)));
+            }
+            for (x, ch) in line.chars().enumerate() {
+                cells.push(cell(ch).ok_or_else(|| SolveError::malformed(first_index + y + 1, x + 1, expected))?);
+            }
+            height += 1;
+        }
+        Ok(Grid{width, height, cells})
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, (x, y):Pos) -> Option<&T> {
+        if x < self.width && y < self.height {
+            self.cells.get(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, (x, y):Pos) -> Option<&mut T> {
+        if x < self.width && y < self.height {
+            self.cells.get_mut(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Sets the cell at `pos`, false when it is outside the grid
+    pub fn set(&mut self, pos:Pos, value:T) -> bool {
+        match self.get_mut(pos) {
+            Some(cell) => {
+                *cell = value;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Position `dx`, `dy` away from `pos`, if it is inside the grid
+    pub fn offset(&self, (x, y):Pos, dx:isize, dy:isize) -> Option<Pos> {
+        let x = x.checked_add_signed(dx)?;
+        let y = y.checked_add_signed(dy)?;
+        if x < self.width && y < self.height {
+            Some((x, y))
+        } else {
+            None
+        }
+    }
+
+    pub fn step(&self, pos:Pos, direction:Direction) -> Option<Pos> {
+        let (dx, dy) = direction.delta();
+        self.offset(pos, dx, dy)
+    }
+
+    /// The up to four positions next to `pos`
+    pub fn neighbors4(&self, pos:Pos) -> impl Iterator<Item=Pos> + '_ {
+        IntoIterator::into_iter(Direction::ALL).filter_map(move |direction| self.step(pos, direction))
+    }
+
+    /// The up to eight positions next to `pos`, diagonals included
+    pub fn neighbors8(&self, pos:Pos) -> impl Iterator<Item=Pos> + '_ {
+        IntoIterator::into_iter([(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)])
+            .filter_map(move |(dx, dy)| self.offset(pos, dx, dy))
+    }
+
+    /// Every position, row by row
+    pub fn positions(&self) -> impl Iterator<Item=Pos> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| (i % width, i / width))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=(Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, y:usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item=&[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x:usize) -> impl Iterator<Item=&T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1)).take(self.height)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item=impl Iterator<Item=&T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// Position of the first cell, row by row, that matches `predicate`
+    pub fn find(&self, predicate:impl Fn(&T) -> bool) -> Option<Pos> {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(pos, _)| pos)
+    }
+
+    pub fn map<U>(&self, f:impl Fn(&T) -> U) -> Grid<U> {
+        Grid{width: self.width, height: self.height, cells: self.cells.iter().map(f).collect()}
+    }
+
+    /// Mirrors the grid in its top-left to bottom-right diagonal, rows become columns
+    pub fn transpose(&self) -> Grid<T> where T:Clone {
+        let cells = self.columns().flat_map(|column| column.cloned()).collect();
+        Grid{width: self.height, height: self.width, cells}
+    }
+
+    pub fn rotate_clockwise(&self) -> Grid<T> where T:Clone {
+        let cells = self.columns().flat_map(|column| column.cloned().collect::<Vec<T>>().into_iter().rev()).collect();
+        Grid{width: self.height, height: self.width, cells}
+    }
+
+    pub fn rotate_counterclockwise(&self) -> Grid<T> where T:Clone {
+        let cells = (0..self.width).rev().flat_map(|x| self.column(x).cloned()).collect();
+        Grid{width: self.height, height: self.width, cells}
+    }
+}
+
+impl Grid<char> {
+    /// Parses a grid of the characters in `allowed`
+    pub fn parse_chars(input:&str, allowed:&str) -> Result<Grid<char>> {
+        Grid::parse(input, |ch| Some(ch).filter(|ch| allowed.contains(*ch)), &format!("expected one of '{}'", allowed))
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos:Pos) -> &T {
+        self.get(pos).unwrap_or_else(|| panic!("{:?} is outside the {}x{} grid", pos, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos:Pos) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(pos).unwrap_or_else(|| panic!("{:?} is outside the {}x{} grid", pos, width, height))
+    }
+}
+
+/// One line per row, the cells of a row written next to each other
+impl<T:fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRID:&str = "ab.\n.#c";
+
+    fn grid() -> Grid<char> {
+        Grid::parse(GRID, Some, "any character").unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = grid();
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        assert_eq!(GRID, grid.to_string());
+        assert_eq!(Some(&'c'), grid.get((2, 1)));
+        assert_eq!(None, grid.get((3, 0)));
+
+        let digits = |ch:char| ch.to_digit(10);
+        assert_eq!(Err(SolveError::malformed(2, 2, "expected a digit")), Grid::parse("12\n3x", digits, "expected a digit"));
+        assert_eq!(Err(SolveError::malformed(2, 1, "expected 2 columns")), Grid::parse("12\n345", digits, "expected a digit"));
+        assert_eq!(Err(SolveError::malformed(5, 1, "empty grid")), Grid::parse_at(4, "", digits, "expected a digit"));
+        assert_eq!(Err(SolveError::malformed(1, 3, "expected one of '.#'")), Grid::parse_chars("#.O", ".#"));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = grid();
+        assert_eq!(vec![(1, 0), (0, 1)], grid.neighbors4((0, 0)).collect::<Vec<Pos>>());
+        assert_eq!(vec![(0, 0), (1, 0), (2, 0), (0, 1), (2, 1)], grid.neighbors8((1, 1)).collect::<Vec<Pos>>());
+        assert_eq!(Some((1, 0)), grid.step((1, 1), Direction::Up));
+        assert_eq!(None, grid.step((1, 1), Direction::Down));
+    }
+
+    #[test]
+    fn test_set_and_find() {
+        let mut grid = grid();
+        assert_eq!(Some((1, 1)), grid.find(|ch| *ch == '#'));
+        assert!(grid.set((1, 1), 'x'));
+        assert!(!grid.set((1, 2), 'x'));
+        grid[(0, 0)] = 'z';
+        assert_eq!("zb.\n.xc", grid.to_string());
+        assert_eq!(None, grid.find(|ch| *ch == '#'));
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = grid();
+        assert_eq!(vec![&['a', 'b', '.'][..], &['.', '#', 'c'][..]], grid.rows().collect::<Vec<&[char]>>());
+        assert_eq!(&['.', '#', 'c'], grid.row(1));
+        assert_eq!(vec!["a.", "b#", ".c"], grid.columns().map(|column| column.collect::<String>()).collect::<Vec<String>>());
+    }
+
+    #[test]
+    fn test_transform() {
+        let grid = grid();
+        assert_eq!("a.\nb#\n.c", grid.transpose().to_string());
+        assert_eq!(".a\n#b\nc.", grid.rotate_clockwise().to_string());
+        assert_eq!(".c\nb#\na.", grid.rotate_counterclockwise().to_string());
+        assert_eq!(grid, grid.rotate_clockwise().rotate_counterclockwise());
+        assert_eq!("001\n100", grid.map(|ch| if *ch == '.' { 1 } else { 0 }).to_string());
+    }
+
+    #[test]
+    fn test_direction() {
+        assert_eq!(Direction::Left, Direction::Up.turn_left());
+        assert_eq!(Direction::Right, Direction::Up.turn_right());
+        assert_eq!(Direction::Down, Direction::Up.opposite());
+        assert!(Direction::Left.is_horizontal());
+    }
+}
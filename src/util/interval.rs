@@ -0,0 +1,383 @@
+use std::cmp::{max, min};
+use std::iter::FromIterator;
+
+/// The integers from `start` up to, but not including, `end`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval {
+    pub start:i64,
+    pub end:i64,
+}
+
+impl Interval {
+    pub const fn new(start:i64, end:i64) -> Interval {
+        Interval{start, end}
+    }
+
+    /// The integers from `first` to `last`, both included
+    pub fn inclusive(first:i64, last:i64) -> Interval {
+        Interval{start: first, end: last + 1}
+    }
+
+    pub fn len(&self) -> u64 {
+        self.end.saturating_sub(self.start).max(0) as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+
+    pub fn contains(&self, value:i64) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    pub fn intersection(&self, other:&Interval) -> Interval {
+        Interval{start: max(self.start, other.start), end: min(self.end, other.end)}
+    }
+
+    /// The values below `value` and those from `value` on, either may be empty
+    pub fn split_at(&self, value:i64) -> (Interval, Interval) {
+        let at = value.clamp(self.start, max(self.start, self.end));
+        (Interval{start: self.start, end: at}, Interval{start: at, end: self.end})
+    }
+
+    pub fn shift(&self, offset:i64) -> Interval {
+        Interval{start: self.start + offset, end: self.end + offset}
+    }
+}
+
+/// A set of integers kept as sorted intervals that neither overlap nor touch, so equal sets are
+/// always stored the same way
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct IntervalSet {
+    intervals:Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> IntervalSet {
+        IntervalSet{intervals: vec![]}
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Number of integers in the set
+    pub fn len(&self) -> u64 {
+        self.intervals.iter().map(|interval| interval.len()).sum()
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.intervals.first().map(|interval| interval.start)
+    }
+
+    pub fn contains(&self, value:i64) -> bool {
+        // The last interval starting at or before `value` is the only one that can hold it
+        let index = self.intervals.partition_point(|interval| interval.start <= value);
+        index > 0 && self.intervals[index - 1].contains(value)
+    }
+
+    pub fn union(&self, other:&IntervalSet) -> IntervalSet {
+        self.intervals.iter().chain(other.intervals.iter()).copied().collect()
+    }
+
+    pub fn intersection(&self, other:&IntervalSet) -> IntervalSet {
+        // Both are sorted, so walk them side by side, moving past whichever interval ends first
+        let (mut i, mut j) = (0, 0);
+        let mut intervals = vec![];
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+            intervals.push(a.intersection(&b));
+            if a.end < b.end { i += 1 } else { j += 1 }
+        }
+        intervals.into_iter().collect()
+    }
+
+    pub fn difference(&self, other:&IntervalSet) -> IntervalSet {
+        let mut intervals = vec![];
+        let mut others = other.intervals.iter().peekable();
+        for interval in self.intervals.iter() {
+            let mut rest = *interval;
+            // Skip what ends before this interval, the rest is cut out of it from the left
+            while others.peek().is_some_and(|other| other.end <= rest.start) {
+                others.next();
+            }
+            let end = rest.end;
+            for other in others.clone().take_while(|other| other.start < end) {
+                intervals.push(Interval::new(rest.start, other.start));
+                rest.start = max(rest.start, other.end);
+            }
+            intervals.push(rest);
+        }
+        intervals.into_iter().collect()
+    }
+
+    /// The values below `value` and those from `value` on
+    pub fn split_at(&self, value:i64) -> (IntervalSet, IntervalSet) {
+        let (below, above):(Vec<Interval>, Vec<Interval>) = self.intervals.iter()
+            .map(|interval| interval.split_at(value))
+            .unzip();
+        (below.into_iter().collect(), above.into_iter().collect())
+    }
+
+    pub fn shift(&self, offset:i64) -> IntervalSet {
+        IntervalSet{intervals: self.intervals.iter().map(|interval| interval.shift(offset)).collect()}
+    }
+}
+
+impl From<Interval> for IntervalSet {
+    fn from(interval:Interval) -> IntervalSet {
+        Some(interval).into_iter().collect()
+    }
+}
+
+/// Sorts the intervals and merges those that overlap or touch, dropping empty ones
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I:IntoIterator<Item=Interval>>(iter:I) -> IntervalSet {
+        let mut sorted = iter.into_iter().filter(|interval| !interval.is_empty()).collect::<Vec<Interval>>();
+        sorted.sort();
+        let mut intervals:Vec<Interval> = vec![];
+        for interval in sorted {
+            match intervals.last_mut() {
+                Some(last) if interval.start <= last.end => last.end = max(last.end, interval.end),
+                _ => intervals.push(interval),
+            }
+        }
+        IntervalSet{intervals}
+    }
+}
+
+/// Maps each integer by adding the offset of the piece it falls in, integers outside every piece map
+/// to themselves.
+///
+/// Pieces are kept sorted, apart and with a non-zero offset, with touching pieces of the same offset
+/// merged, so equal maps are always stored the same way.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct PiecewiseMap {
+    pieces:Vec<(Interval, i64)>,
+}
+
+impl PiecewiseMap {
+    /// The map taking every integer to itself
+    pub fn identity() -> PiecewiseMap {
+        PiecewiseMap{pieces: vec![]}
+    }
+
+    /// Map of the pieces, each an interval and the offset added to the values in it. Where pieces
+    /// overlap the one that comes first applies.
+    pub fn new(pieces:impl IntoIterator<Item=(Interval, i64)>) -> PiecewiseMap {
+        let mut covered = IntervalSet::new();
+        let mut disjoint = vec![];
+        for (interval, offset) in pieces {
+            let uncovered = IntervalSet::from(interval).difference(&covered);
+            disjoint.extend(uncovered.intervals().iter().map(|interval| (*interval, offset)));
+            covered = covered.union(&uncovered);
+        }
+        PiecewiseMap::normalized(disjoint)
+    }
+
+    // Sorts pieces that don't overlap and merges or drops them as `PiecewiseMap` promises
+    fn normalized(mut disjoint:Vec<(Interval, i64)>) -> PiecewiseMap {
+        disjoint.retain(|(interval, offset)| !interval.is_empty() && *offset != 0);
+        disjoint.sort();
+        let mut pieces:Vec<(Interval, i64)> = vec![];
+        for (interval, offset) in disjoint {
+            match pieces.last_mut() {
+                Some((last, last_offset)) if last.end == interval.start && *last_offset == offset => last.end = interval.end,
+                _ => pieces.push((interval, offset)),
+            }
+        }
+        PiecewiseMap{pieces}
+    }
+
+    pub fn pieces(&self) -> &[(Interval, i64)] {
+        &self.pieces
+    }
+
+    /// The integers the pieces cover, those that don't map to themselves
+    pub fn domain(&self) -> IntervalSet {
+        self.pieces.iter().map(|(interval, _)| *interval).collect()
+    }
+
+    pub fn apply(&self, value:i64) -> i64 {
+        let index = self.pieces.partition_point(|(interval, _)| interval.start <= value);
+        match self.pieces.get(index.wrapping_sub(1)) {
+            Some((interval, offset)) if interval.contains(value) => value + offset,
+            _ => value,
+        }
+    }
+
+    /// Every value of `set` mapped
+    pub fn apply_set(&self, set:&IntervalSet) -> IntervalSet {
+        let unmapped = set.difference(&self.domain());
+        let mapped = self.pieces.iter()
+            .flat_map(|(interval, offset)| set.intersection(&IntervalSet::from(*interval)).shift(*offset).intervals().to_vec());
+        unmapped.intervals().iter().copied().chain(mapped).collect()
+    }
+
+    /// The map that applies this map and then `then`
+    pub fn compose(&self, then:&PiecewiseMap) -> PiecewiseMap {
+        // Through a piece of this map, the values end up in pieces of `then` or outside all of them
+        let through = self.pieces.iter().flat_map(|(interval, offset)| {
+            let moved = IntervalSet::from(interval.shift(*offset));
+            let outside = moved.difference(&then.domain()).intervals().iter()
+                .map(|part| (part.shift(-offset), *offset))
+                .collect::<Vec<(Interval, i64)>>();
+            then.pieces.iter()
+                .map(|(then_interval, then_offset)| (interval.shift(*offset).intersection(then_interval).shift(-offset), offset + then_offset))
+                .chain(outside)
+                .collect::<Vec<(Interval, i64)>>()
+        });
+        // Values outside every piece of this map only move by `then`
+        let domain = self.domain();
+        let only_then = then.pieces.iter().flat_map(|(interval, offset)| IntervalSet::from(*interval)
+            .difference(&domain).intervals().iter()
+            .map(|part| (*part, *offset))
+            .collect::<Vec<(Interval, i64)>>());
+        PiecewiseMap::normalized(through.chain(only_then).collect())
+    }
+
+    /// The map undoing this one, if no two integers map to the same one. That is when the pieces
+    /// move their values onto exactly the integers they cover.
+    pub fn invert(&self) -> Option<PiecewiseMap> {
+        let images = self.pieces.iter().map(|(interval, offset)| interval.shift(*offset)).collect::<Vec<Interval>>();
+        let image_set = images.iter().copied().collect::<IntervalSet>();
+        let overlapping = image_set.len() != images.iter().map(|image| image.len()).sum::<u64>();
+        if overlapping || image_set != self.domain() {
+            return None;
+        }
+        Some(PiecewiseMap::normalized(images.into_iter().zip(self.pieces.iter()).map(|(image, (_, offset))| (image, -offset)).collect()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Linear congruential generator, so the random cases are the same on every run
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, bound:u64) -> u64 {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (self.0 >> 33) % bound
+        }
+
+        // A few random intervals within 0..64, possibly overlapping or empty
+        fn intervals(&mut self) -> Vec<Interval> {
+            (0..self.next(5)).map(|_| {
+                let start = self.next(64) as i64;
+                Interval::new(start, start + self.next(20) as i64)
+            }).collect()
+        }
+    }
+
+    // The set as a bit per value, values from 64 on are left out
+    fn bits(set:&IntervalSet) -> u64 {
+        (0..64).filter(|value| set.contains(*value)).fold(0, |bits, value| bits | 1 << value)
+    }
+
+    fn is_normalized(set:&IntervalSet) -> bool {
+        set.intervals().iter().all(|interval| !interval.is_empty())
+            && set.intervals().windows(2).all(|pair| pair[0].end < pair[1].start)
+    }
+
+    #[test]
+    fn test_interval() {
+        let interval = Interval::inclusive(3, 7);
+        assert_eq!((5, false), (interval.len(), interval.is_empty()));
+        assert!(interval.contains(7) && !interval.contains(8));
+        assert_eq!((Interval::new(3, 5), Interval::new(5, 8)), interval.split_at(5));
+        assert_eq!((Interval::new(3, 3), Interval::new(3, 8)), interval.split_at(0));
+        assert!(interval.intersection(&Interval::new(8, 10)).is_empty());
+    }
+
+    #[test]
+    fn test_interval_set() {
+        let set = IntervalSet::from_iter(vec![Interval::new(5, 8), Interval::new(0, 2), Interval::new(2, 3), Interval::new(7, 9)]);
+        assert_eq!(&[Interval::new(0, 3), Interval::new(5, 9)], set.intervals());
+        assert_eq!((7, Some(0)), (set.len(), set.min()));
+        let (below, above) = set.split_at(6);
+        assert_eq!(&[Interval::new(0, 3), Interval::new(5, 6)], below.intervals());
+        assert_eq!(&[Interval::new(6, 9)], above.intervals());
+        assert_eq!(&[Interval::new(0, 3), Interval::new(8, 9)], set.difference(&IntervalSet::from(Interval::new(4, 8))).intervals());
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let mut random = Lcg(2023);
+        for _ in 0..2000 {
+            let a = random.intervals().into_iter().collect::<IntervalSet>();
+            let b = random.intervals().into_iter().collect::<IntervalSet>();
+            let value = random.next(70) as i64;
+            let (below, above) = a.split_at(value);
+
+            for set in [&a, &b, &a.union(&b), &a.intersection(&b), &a.difference(&b), &below, &above] {
+                assert!(is_normalized(set), "{:?}", set);
+            }
+            assert_eq!(bits(&a) | bits(&b), bits(&a.union(&b)));
+            assert_eq!(bits(&a) & bits(&b), bits(&a.intersection(&b)));
+            assert_eq!(bits(&a) & !bits(&b), bits(&a.difference(&b)));
+            let below_value = if value < 64 { (1 << value) - 1 } else { u64::MAX };
+            assert_eq!(bits(&a) & below_value, bits(&below));
+            assert_eq!(a, below.union(&above));
+            assert!(below.intersection(&above).is_empty());
+            assert_eq!(a.len(), (0..90).filter(|value| a.contains(*value)).count() as u64);
+        }
+    }
+
+    #[test]
+    fn test_piecewise_map() {
+        // The first piece wins where they overlap
+        let map = PiecewiseMap::new(vec![(Interval::new(0, 5), 10), (Interval::new(3, 8), 10), (Interval::new(8, 9), 0)]);
+        assert_eq!(&[(Interval::new(0, 8), 10)], map.pieces());
+        assert_eq!((10, 17, 8), (map.apply(0), map.apply(7), map.apply(8)));
+        assert_eq!(&[Interval::new(8, 9), Interval::new(10, 18)], map.apply_set(&IntervalSet::from(Interval::new(0, 9))).intervals());
+        assert_eq!(None, map.invert());
+
+        let swap = PiecewiseMap::new(vec![(Interval::new(0, 5), 5), (Interval::new(5, 10), -5)]);
+        assert_eq!(Some(swap.clone()), swap.invert());
+        assert_eq!(PiecewiseMap::identity(), swap.compose(&swap));
+    }
+
+    // A random map within 0..64, possibly overlapping and with pieces moved outside 0..64
+    fn random_map(random:&mut Lcg) -> PiecewiseMap {
+        PiecewiseMap::new(random.intervals().into_iter().map(|interval| (interval, random.next(40) as i64 - 20)))
+    }
+
+    // A map that permutes 0..64 by swapping two blocks of the same length that don't overlap
+    fn random_permutation(random:&mut Lcg) -> PiecewiseMap {
+        let length = 1 + random.next(16) as i64;
+        let first = random.next(64 - 2 * length as u64 + 1) as i64;
+        let second = first + length + random.next((64 - first - 2 * length + 1) as u64) as i64;
+        PiecewiseMap::new(vec![(Interval::new(first, first + length), second - first), (Interval::new(second, second + length), first - second)])
+    }
+
+    #[test]
+    fn test_map_algebra() {
+        let mut random = Lcg(25);
+        for _ in 0..1000 {
+            let (first, second) = (random_map(&mut random), random_map(&mut random));
+            let set = random.intervals().into_iter().collect::<IntervalSet>();
+            let composed = first.compose(&second);
+
+            let mapped = set.intervals().iter().flat_map(|interval| interval.start..interval.end)
+                .map(|value| Interval::new(first.apply(value), first.apply(value) + 1))
+                .collect::<IntervalSet>();
+            assert_eq!(mapped, first.apply_set(&set));
+            for value in -30..90 {
+                assert_eq!(second.apply(first.apply(value)), composed.apply(value), "{:?} then {:?} at {}", first, second, value);
+            }
+            assert_eq!(composed, PiecewiseMap::new(composed.pieces().to_vec()));
+
+            let permutation = random_permutation(&mut random).compose(&random_permutation(&mut random));
+            let inverse = permutation.invert().unwrap();
+            assert!((-10..74).all(|value| inverse.apply(permutation.apply(value)) == value));
+            assert_eq!(PiecewiseMap::identity(), permutation.compose(&inverse));
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::fmt;
+use std::ops::{Div, Mul, Rem, Sub};
+
+/// The primitive integer types `gcd` and `lcm` work on
+pub trait Integer: Copy + Ord + Rem<Output=Self> + Div<Output=Self> + Mul<Output=Self> + Sub<Output=Self> {
+    const ZERO:Self;
+    const ONE:Self;
+
+    fn checked_mul(self, other:Self) -> Option<Self>;
+}
+
+macro_rules! integer {
+    ($($t:ty),*) => {
+        $(impl Integer for $t {
+            const ZERO:$t = 0;
+            const ONE:$t = 1;
+
+            fn checked_mul(self, other:$t) -> Option<$t> {
+                <$t>::checked_mul(self, other)
+            }
+        })*
+    }
+}
+
+integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Arithmetic that doesn't fit the integer type
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "integer arithmetic overflowed")
+    }
+}
+
+/// Greatest common divisor, never negative and 0 only when both are 0
+pub fn gcd<T:Integer>(a:T, b:T) -> T {
+    let (mut a, mut b) = (a, b);
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+    if a < T::ZERO { T::ZERO - a } else { a }
+}
+
+/// Least common multiple, 0 if either is 0. Panics if it doesn't fit, see `checked_lcm`.
+pub fn lcm<T:Integer>(a:T, b:T) -> T {
+    checked_lcm(a, b).expect("least common multiple overflowed")
+}
+
+/// Least common multiple, `None` if it doesn't fit
+pub fn checked_lcm<T:Integer>(a:T, b:T) -> Option<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Some(T::ZERO);
+    }
+    // Dividing first keeps the intermediate value no larger than the result
+    let multiple = (a / gcd(a, b)).checked_mul(b)?;
+    Some(if multiple < T::ZERO { T::ZERO - multiple } else { multiple })
+}
+
+/// Least common multiple of all the values, 1 if there are none and `None` if it doesn't fit
+pub fn checked_lcm_all<T:Integer>(values:impl IntoIterator<Item=T>) -> Option<T> {
+    values.into_iter().try_fold(T::ONE, checked_lcm)
+}
+
+/// The greatest common divisor `g` of `a` and `b` with `x` and `y` such that `a * x + b * y == g`
+pub fn extended_gcd(a:i128, b:i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+    if old_r < 0 { (-old_r, -old_x, -old_y) } else { (old_r, old_x, old_y) }
+}
+
+/// `x` in `0..modulus` with `a * x` one more than a multiple of `modulus`, if `a` and `modulus`
+/// share no divisor
+pub fn mod_inverse(a:i128, modulus:i128) -> Option<i128> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(modulus), modulus);
+    if g == 1 { Some(x.rem_euclid(modulus)) } else { None }
+}
+
+/// The integers `residue` more than a multiple of `modulus`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Congruence {
+    pub residue:i128,
+    pub modulus:i128,
+}
+
+impl Congruence {
+    /// Kept with the residue in `0..modulus`, the modulus must be positive
+    pub fn new(residue:i128, modulus:i128) -> Congruence {
+        assert!(modulus > 0, "congruence modulo {}", modulus);
+        Congruence{residue: residue.rem_euclid(modulus), modulus}
+    }
+
+    /// The integers satisfying both congruences, by the Chinese remainder theorem. The moduli may
+    /// share divisors, in which case the congruences may contradict each other and there is no
+    /// solution. An error if the combined modulus or an intermediate value doesn't fit.
+    pub fn checked_merge(self, other:Congruence) -> Result<Option<Congruence>, Overflow> {
+        // x = self.residue + self.modulus * k, solve for k modulo what the moduli don't share
+        let (g, inverse, _) = extended_gcd(self.modulus, other.modulus);
+        let difference = other.residue - self.residue;
+        if difference % g != 0 {
+            return Ok(None);
+        }
+        let step = other.modulus / g;
+        let k = ((difference / g).rem_euclid(step)).checked_mul(inverse.rem_euclid(step)).ok_or(Overflow)?.rem_euclid(step);
+        let modulus = self.modulus.checked_mul(step).ok_or(Overflow)?;
+        let residue = self.modulus.checked_mul(k).and_then(|offset| offset.checked_add(self.residue)).ok_or(Overflow)?;
+        Ok(Some(Congruence::new(residue, modulus)))
+    }
+
+    /// The smallest of the integers no less than `start`, an error if it doesn't fit
+    pub fn first_at_least(self, start:i128) -> Result<i128, Overflow> {
+        self.residue.checked_sub(start)
+            .and_then(|behind| start.checked_add(behind.rem_euclid(self.modulus)))
+            .ok_or(Overflow)
+    }
+}
+
+/// The integers satisfying every congruence, by the Chinese remainder theorem. The moduli may
+/// share divisors, in which case the congruences may contradict each other and there is no
+/// solution. Panics on overflow, see `checked_crt`.
+pub fn crt(congruences:&[Congruence]) -> Option<Congruence> {
+    checked_crt(congruences).expect("chinese remainder overflowed i128")
+}
+
+/// Like `crt`, with an error if the combined modulus or an intermediate value doesn't fit
+pub fn checked_crt(congruences:&[Congruence]) -> Result<Option<Congruence>, Overflow> {
+    let mut combined = Congruence::new(0, 1);
+    for congruence in congruences {
+        match combined.checked_merge(*congruence)? {
+            Some(merged) => combined = merged,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(combined))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(6, gcd(54u32, 24));
+        assert_eq!(6, gcd(-54i64, 24));
+        assert_eq!(7, gcd(0usize, 7));
+        assert_eq!(0, gcd(0u8, 0));
+        assert_eq!(216, lcm(54u64, 24));
+        assert_eq!(0, lcm(0i32, 5));
+        assert_eq!(None, checked_lcm(u8::MAX, 2));
+        assert_eq!(Some(255), checked_lcm(255u8, 5));
+        assert_eq!(Some(2520), checked_lcm_all(1..=10u64));
+        assert_eq!(Some(1), checked_lcm_all(Vec::<u64>::new()));
+        assert_eq!(None, checked_lcm_all(1..=100u64));
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        for (a, b) in [(240, 46), (-240, 46), (17, 0), (0, -5), (1071, 462)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(gcd(a, b), g);
+            assert_eq!(g, a * x + b * y);
+        }
+        assert_eq!(Some(4), mod_inverse(3, 11));
+        assert_eq!(Some(7), mod_inverse(-3, 11));
+        assert_eq!(None, mod_inverse(6, 9));
+    }
+
+    #[test]
+    fn test_merge() {
+        let merge = |(a, first), (b, second)| Congruence::new(a, first).checked_merge(Congruence::new(b, second)).unwrap();
+        assert_eq!(Some(Congruence::new(23, 105)), merge((23, 15), (2, 7)));
+        // Moduli sharing a divisor, first consistent and then not
+        assert_eq!(Some(Congruence::new(10, 12)), merge((4, 6), (2, 4)));
+        assert_eq!(None, merge((1, 6), (2, 4)));
+        assert_eq!(Some(Congruence::new(3, 5)), merge((0, 1), (3, 5)));
+
+        // Against trying every value for small moduli
+        for (first, second) in [(4, 6), (9, 12), (5, 7), (8, 8)] {
+            for (a, b) in [(0, 0), (1, 3), (3, 5), (2, 7)] {
+                let expected = (0..first * second).find(|x| x % first == a % first && x % second == b % second);
+                assert_eq!(expected, merge((a, first), (b, second)).map(|congruence| congruence.residue));
+            }
+        }
+
+        let huge = i128::MAX / 3;
+        assert_eq!(Err(Overflow), Congruence::new(1, huge).checked_merge(Congruence::new(2, huge - 1)));
+    }
+
+    #[test]
+    fn test_crt() {
+        let solve = |pairs:&[(i128, i128)]| crt(&pairs.iter().map(|(residue, modulus)| Congruence::new(*residue, *modulus)).collect::<Vec<Congruence>>());
+        assert_eq!(Some(Congruence::new(23, 105)), solve(&[(2, 3), (3, 5), (2, 7)]));
+        assert_eq!(None, solve(&[(1, 6), (2, 4), (3, 5)]));
+        assert_eq!(Some(Congruence::new(0, 1)), solve(&[]));
+
+        let huge = i128::MAX / 3;
+        assert_eq!(Err(Overflow), checked_crt(&[Congruence::new(1, huge), Congruence::new(2, huge - 1)]));
+    }
+
+    #[test]
+    fn test_first_at_least() {
+        let congruence = Congruence::new(3, 7);
+        assert_eq!(Ok(3), congruence.first_at_least(0));
+        assert_eq!(Ok(3), congruence.first_at_least(3));
+        assert_eq!(Ok(10), congruence.first_at_least(4));
+        assert_eq!(Ok(-4), congruence.first_at_least(-10));
+        assert_eq!(Err(Overflow), congruence.first_at_least(i128::MAX - 1));
+    }
+}
@@ -0,0 +1,96 @@
+#[cfg(feature = "parallel")]
+use std::cell::Cell;
+#[cfg(feature = "parallel")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "parallel")]
+use std::thread;
+
+#[cfg(feature = "parallel")]
+thread_local! {
+    // Set on the threads `map_on` spawns
+    static IN_WORKER:Cell<bool> = const { Cell::new(false) };
+}
+
+/// `f` applied to every item, the results in the order of the items.
+///
+/// With the `parallel` feature the items are shared out over one thread per core, each taking the next
+/// item not yet taken so slow items don't hold up the rest, and the results are put back in order.
+/// A `map` called from inside another, like day 16 solved by `all` on one of its threads, runs on the
+/// thread it was called from so there are never more threads than cores. Without the feature the items
+/// are mapped one after the other. Either way the result is the same.
+pub fn map<T:Sync, R:Send>(items:&[T], f:impl Fn(&T) -> R + Sync) -> Vec<R> {
+    #[cfg(feature = "parallel")]
+    {
+        if !IN_WORKER.with(Cell::get) {
+            return map_on(thread::available_parallelism().map_or(1, |threads| threads.get()), items, f);
+        }
+    }
+    items.iter().map(f).collect()
+}
+
+// Like `map` on at most `threads` threads
+#[cfg(feature = "parallel")]
+fn map_on<T:Sync, R:Send>(threads:usize, items:&[T], f:impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let threads = threads.min(items.len());
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let mut results = thread::scope(|scope| {
+        let workers = (0..threads)
+            .map(|_| scope.spawn(|| {
+                IN_WORKER.with(|in_worker| in_worker.set(true));
+                let mut results = vec![];
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    match items.get(index) {
+                        Some(item) => results.push((index, f(item))),
+                        None => return results,
+                    }
+                }
+            }))
+            .collect::<Vec<_>>();
+        workers.into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect::<Vec<(usize, R)>>()
+    });
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map() {
+        let items = (0..1000).collect::<Vec<u64>>();
+        let squares = map(&items, |item| item * item);
+        assert_eq!(items.iter().map(|item| item * item).collect::<Vec<u64>>(), squares);
+        assert_eq!(Vec::<u64>::new(), map(&[], |item:&u64| *item));
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn test_map_on_threads() {
+        // Uneven work, so the threads finish their items out of order
+        let items = (0..200).collect::<Vec<u64>>();
+        let slow_square = |item:&u64| (0..(item % 7) * 1000).fold(item * item, |square, _| std::hint::black_box(square));
+        assert_eq!(map(&items, slow_square), map_on(4, &items, slow_square));
+        assert_eq!(vec![1], map_on(4, &[1], |item| *item));
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn test_nested_map() {
+        // The inner maps run on the thread of the outer item
+        let outer = map_on(4, &[0, 1, 2, 3], |_| {
+            let caller = thread::current().id();
+            map(&[0, 1, 2, 3], |_| thread::current().id() == caller)
+        });
+        assert!(outer.into_iter().flatten().all(|same_thread| same_thread));
+        assert!(!IN_WORKER.with(Cell::get));
+    }
+}
@@ -0,0 +1,204 @@
+use std::cmp::Ordering;
+use std::fmt;
+use super::math;
+
+/// Exact fraction, always in lowest terms with a positive denominator so equal values compare equal.
+///
+/// Numerator and denominator are `i128`, wide enough for products of a few puzzle sized `i64`s.
+/// Arithmetic is checked like `i128::checked_add`, giving `None` rather than losing precision when
+/// the result doesn't fit. Comparing never overflows.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator:i128,
+    denominator:i128,
+}
+
+impl Rational {
+    pub fn new(numerator:i128, denominator:i128) -> Rational {
+        assert_ne!(0, denominator, "rational with a zero denominator");
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+        Rational{numerator: numerator / divisor, denominator: denominator / divisor}
+    }
+
+    pub fn integer(value:i128) -> Rational {
+        Rational{numerator: value, denominator: 1}
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == 1
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.numerator < 0
+    }
+
+    /// Largest integer not above the value
+    pub fn floor(&self) -> i128 {
+        self.numerator.div_euclid(self.denominator)
+    }
+
+    /// Closest `f64`, for display only as it may round
+    pub fn to_f64(self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    pub fn checked_add(self, other:Rational) -> Option<Rational> {
+        // Only scaling up to the least common denominator keeps intermediate values small
+        let divisor = gcd(self.denominator, other.denominator);
+        let (self_scale, other_scale) = (other.denominator / divisor, self.denominator / divisor);
+        let numerator = self.numerator.checked_mul(self_scale)?.checked_add(other.numerator.checked_mul(other_scale)?)?;
+        Some(Rational::new(numerator, self.denominator.checked_mul(self_scale)?))
+    }
+
+    pub fn checked_neg(self) -> Option<Rational> {
+        Some(Rational{numerator: self.numerator.checked_neg()?, denominator: self.denominator})
+    }
+
+    pub fn checked_sub(self, other:Rational) -> Option<Rational> {
+        self.checked_add(other.checked_neg()?)
+    }
+
+    pub fn checked_mul(self, other:Rational) -> Option<Rational> {
+        // Cancelling across before multiplying keeps intermediate values small
+        let first = gcd(self.numerator, other.denominator);
+        let second = gcd(other.numerator, self.denominator);
+        let numerator = (self.numerator / first).checked_mul(other.numerator / second)?;
+        let denominator = (self.denominator / second).checked_mul(other.denominator / first)?;
+        Some(Rational::new(numerator, denominator))
+    }
+
+    /// `None` when dividing by zero too
+    pub fn checked_div(self, other:Rational) -> Option<Rational> {
+        if other.numerator == 0 {
+            return None;
+        }
+        self.checked_mul(Rational::new(other.denominator, other.numerator))
+    }
+}
+
+// Greatest common divisor, at least 1 so it can always be divided by
+fn gcd(a:i128, b:i128) -> i128 {
+    math::gcd(a, b).max(1)
+}
+
+impl From<i64> for Rational {
+    fn from(value:i64) -> Rational {
+        Rational::integer(value as i128)
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other:&Rational) -> Ordering {
+        // Cross multiplying could overflow, so compare the integer parts and then the fractions left
+        // over, which are below 1. Those compare the other way round to their reciprocals, which in turn
+        // have integer parts and smaller fractions, shrinking like Euclid's algorithm.
+        let (mut first, mut second) = ((self.numerator, self.denominator), (other.numerator, other.denominator));
+        let mut reversed = false;
+        let order = loop {
+            let integer_order = first.0.div_euclid(first.1).cmp(&second.0.div_euclid(second.1));
+            match (integer_order, first.0.rem_euclid(first.1), second.0.rem_euclid(second.1)) {
+                (Ordering::Equal, 0, 0) => break Ordering::Equal,
+                (Ordering::Equal, 0, _) => break Ordering::Less,
+                (Ordering::Equal, _, 0) => break Ordering::Greater,
+                (Ordering::Equal, first_rest, second_rest) => {
+                    (first, second) = ((first.1, first_rest), (second.1, second_rest));
+                    reversed = !reversed;
+                },
+                (order, _, _) => break order,
+            }
+        };
+        if reversed { order.reverse() } else { order }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other:&Rational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Written as `numerator/denominator`, or just the numerator for integers
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalized() {
+        assert_eq!(Rational::new(1, 2), Rational::new(-3, -6));
+        assert_eq!((-2, 3), (Rational::new(4, -6).numerator(), Rational::new(4, -6).denominator()));
+        assert_eq!(Rational::integer(0), Rational::new(0, -5));
+        assert!(Rational::new(8, 4).is_integer());
+        assert_eq!("-2/3", Rational::new(4, -6).to_string());
+        assert_eq!("7", Rational::from(7).to_string());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let (half, third) = (Rational::new(1, 2), Rational::new(1, 3));
+        assert_eq!(Some(Rational::new(5, 6)), half.checked_add(third));
+        assert_eq!(Some(Rational::new(1, 6)), half.checked_sub(third));
+        assert_eq!(Some(Rational::new(1, 6)), half.checked_mul(third));
+        assert_eq!(Some(Rational::new(3, 2)), half.checked_div(third));
+        assert_eq!(None, half.checked_div(Rational::integer(0)));
+        assert_eq!(Some(Rational::new(-1, 2)), half.checked_neg());
+        assert_eq!(-1, Rational::new(-1, 2).floor());
+        assert_eq!(1, Rational::new(3, 2).floor());
+    }
+
+    #[test]
+    fn test_order() {
+        assert!(Rational::new(1, 3) < Rational::new(1, 2));
+        assert!(Rational::new(-1, 2) < Rational::new(-1, 3));
+        // Neighbours too close for f64 to tell apart
+        let big = Rational::from(400000000000000);
+        let just_above = big.checked_add(Rational::new(1, 1000000)).unwrap();
+        assert_eq!(big.to_f64(), just_above.to_f64());
+        assert!(big < just_above);
+
+        // Cross multiplying these would overflow
+        let (huge, nearly) = (Rational::new(i128::MAX, i128::MAX - 1), Rational::new(i128::MAX - 1, i128::MAX - 2));
+        assert!(huge < nearly);
+        assert!(Rational::new(-i128::MAX, i128::MAX - 1) > Rational::new(1 - i128::MAX, i128::MAX - 2));
+        assert_eq!(Ordering::Equal, huge.cmp(&huge));
+        assert!(Rational::integer(i128::MAX) > huge);
+    }
+
+    #[test]
+    fn test_order_exhaustive() {
+        let fractions = (-6..=6).flat_map(|numerator| (1..=6).map(move |denominator| Rational::new(numerator, denominator)))
+            .collect::<Vec<Rational>>();
+        for a in fractions.iter() {
+            for b in fractions.iter() {
+                assert_eq!((a.numerator * b.denominator).cmp(&(b.numerator * a.denominator)), a.cmp(b), "{} against {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        let huge = Rational::integer(i128::MAX / 2);
+        assert_eq!(None, huge.checked_mul(Rational::integer(3)));
+        assert_eq!(None, huge.checked_add(huge).and_then(|sum| sum.checked_add(huge)));
+        assert_eq!(None, Rational::integer(i128::MIN).checked_neg());
+        assert_eq!(None, Rational::new(1, i128::MAX).checked_add(Rational::new(1, i128::MAX - 1)));
+    }
+}
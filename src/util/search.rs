@@ -0,0 +1,176 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Distances from the start states to every state a search reached, and the state each was reached from
+#[derive(Debug, Clone)]
+pub struct Search<S, C> {
+    pub distances:HashMap<S, C>,
+    previous:HashMap<S, S>,
+    /// The goal state the search stopped at, if it had a goal and reached it
+    pub goal:Option<S>,
+}
+
+impl<S:Clone + Eq + Hash, C:Copy> Search<S, C> {
+    fn new() -> Search<S, C> {
+        Search{distances: HashMap::new(), previous: HashMap::new(), goal: None}
+    }
+
+    pub fn distance(&self, state:&S) -> Option<C> {
+        self.distances.get(state).copied()
+    }
+
+    pub fn goal_distance(&self) -> Option<C> {
+        self.goal.as_ref().and_then(|goal| self.distance(goal))
+    }
+
+    /// States from a start state to `state`, both included
+    pub fn path(&self, state:&S) -> Option<Vec<S>> {
+        if !self.distances.contains_key(state) {
+            return None;
+        }
+
+        let mut path = vec![state.clone()];
+        while let Some(previous) = self.previous.get(path.last().unwrap()) {
+            path.push(previous.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    pub fn goal_path(&self) -> Option<Vec<S>> {
+        self.goal.as_ref().and_then(|goal| self.path(goal))
+    }
+}
+
+/// Breadth first search counting steps, visits every state reachable from `starts`
+pub fn bfs<S, I>(starts:impl IntoIterator<Item=S>, mut neighbors:impl FnMut(&S) -> I) -> Search<S, usize>
+    where S:Clone + Eq + Hash, I:IntoIterator<Item=S> {
+    let mut search = Search::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if search.distances.insert(start.clone(), 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(state) = queue.pop_front() {
+        let distance = search.distances[&state];
+        for next in neighbors(&state) {
+            if let Entry::Vacant(entry) = search.distances.entry(next.clone()) {
+                entry.insert(distance + 1);
+                search.previous.insert(next.clone(), state.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    search
+}
+
+/// Cheapest paths from `starts` over `neighbors` with their step costs, stops at the first state
+/// `is_goal` accepts or when every reachable state is visited
+pub fn dijkstra<S, C, I>(starts:impl IntoIterator<Item=S>, neighbors:impl FnMut(&S) -> I, is_goal:impl FnMut(&S) -> bool) -> Search<S, C>
+    where S:Clone + Eq + Hash, C:Copy + Ord + Default + Add<Output=C>, I:IntoIterator<Item=(S, C)> {
+    astar(starts, neighbors, |_| C::default(), is_goal)
+}
+
+/// Like `dijkstra`, visiting states in order of cost plus `heuristic`. A state is never visited again,
+/// so the heuristic must be consistent: never more than the cost of a step plus the heuristic after it,
+/// and 0 at a goal. Only never overestimating the remaining cost isn't enough.
+pub fn astar<S, C, I>(starts:impl IntoIterator<Item=S>, mut neighbors:impl FnMut(&S) -> I,
+                      mut heuristic:impl FnMut(&S) -> C, mut is_goal:impl FnMut(&S) -> bool) -> Search<S, C>
+    where S:Clone + Eq + Hash, C:Copy + Ord + Default + Add<Output=C>, I:IntoIterator<Item=(S, C)> {
+    let mut search = Search::new();
+    let mut visited = HashSet::new();
+    // States are kept apart so they need no ordering, ties are broken by insertion order
+    let mut states = vec![];
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        if search.distances.insert(start.clone(), C::default()).is_none() {
+            queue.push(Reverse((heuristic(&start), states.len())));
+            states.push(start);
+        }
+    }
+
+    while let Some(Reverse((_, index))) = queue.pop() {
+        let state = states[index].clone();
+        if !visited.insert(state.clone()) {
+            continue;
+        }
+        if is_goal(&state) {
+            search.goal = Some(state);
+            break;
+        }
+
+        let distance = search.distances[&state];
+        for (next, cost) in neighbors(&state) {
+            let next_distance = distance + cost;
+            if search.distances.get(&next).is_some_and(|known| *known <= next_distance) {
+                continue;
+            }
+            search.distances.insert(next.clone(), next_distance);
+            search.previous.insert(next.clone(), state.clone());
+            queue.push(Reverse((next_distance + heuristic(&next), states.len())));
+            states.push(next);
+        }
+    }
+    search
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use util::grid::{Grid, Pos};
+
+    const MAZE:&str = "S...
+.##.
+...E";
+
+    fn maze() -> Grid<char> {
+        Grid::parse_chars(MAZE, "S.#E").unwrap()
+    }
+
+    fn open(maze:&Grid<char>, pos:Pos) -> Vec<(Pos, u32)> {
+        maze.neighbors4(pos).filter(|next| maze[*next] != '#').map(|next| (next, 1)).collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let maze = maze();
+        let search = bfs(vec![(0, 0)], |pos| open(&maze, *pos).into_iter().map(|(next, _)| next));
+        assert_eq!(Some(5), search.distance(&(3, 2)));
+        assert_eq!(Some(3), search.distance(&(3, 0)));
+        assert_eq!(None, search.distance(&(1, 1)));
+        assert_eq!(Some(vec![(0, 0), (1, 0), (2, 0), (3, 0), (3, 1), (3, 2)]), search.path(&(3, 2)));
+        assert_eq!(None, search.goal);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let maze = maze();
+        // Entering the bottom row is expensive, so the cheapest path goes along the top
+        let costs = |pos:&Pos| open(&maze, *pos).into_iter()
+            .map(|(next, _)| (next, if next.1 == 2 { 10 } else { 1 }))
+            .collect::<Vec<(Pos, u32)>>();
+        let search = dijkstra(vec![(0, 0)], costs, |pos| *pos == (3, 2));
+        assert_eq!(Some((3, 2)), search.goal);
+        assert_eq!(Some(14), search.goal_distance());
+        assert_eq!(Some(vec![(0, 0), (1, 0), (2, 0), (3, 0), (3, 1), (3, 2)]), search.goal_path());
+
+        let search = dijkstra(vec![(0, 0)], |pos| open(&maze, *pos), |_| false);
+        assert_eq!(None, search.goal_distance());
+        assert_eq!(10, search.distances.len());
+    }
+
+    #[test]
+    fn test_astar() {
+        let maze = maze();
+        let manhattan = |(x, y):&Pos| (3 - *x + 2 - *y) as u32;
+        let search = astar(vec![(0, 0)], |pos| open(&maze, *pos), manhattan, |pos| maze[*pos] == 'E');
+        assert_eq!(Some(5), search.goal_distance());
+        assert_eq!(Some((0, 0)), search.goal_path().and_then(|path| path.first().copied()));
+    }
+}
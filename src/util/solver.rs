@@ -0,0 +1,108 @@
+use super::{Part, Result};
+
+/// Example input from the puzzle text with the answer it should give for `part`
+#[derive(Debug, Copy, Clone)]
+pub struct Example {
+    pub part:Part,
+    pub input:&'static str,
+    pub expected:&'static str,
+}
+
+/// Input of one day parsed into its model, solving either part without parsing again
+pub trait Parsed {
+    fn solve(&self, part:Part) -> Result<String>;
+}
+
+/// Solution of one day, every day module registers one in `SOLVERS`
+pub trait Solver: Sync {
+    fn day(&self) -> u8;
+
+    fn title(&self) -> &'static str;
+
+    /// Parses the input once, to solve both parts from it or time parsing on its own
+    fn parse_input<'a>(&'a self, input:&str) -> Result<Box<dyn Parsed + 'a>>;
+
+    fn examples(&self) -> &'static [Example] {
+        &[]
+    }
+
+    /// Parses the input and solves one part, use `parse_input` when solving both
+    fn solve(&self, part:Part, input:&str) -> Result<String> {
+        self.parse_input(input)?.solve(part)
+    }
+}
+
+/// A day split into parsing the input into a typed model and solving both parts on that model
+pub trait Puzzle: Sync {
+    type Input;
+
+    fn day(&self) -> u8;
+
+    fn title(&self) -> &'static str;
+
+    fn parse(&self, input:&str) -> Result<Self::Input>;
+
+    fn part1(&self, input:&Self::Input) -> Result<String>;
+
+    /// Day 25 has no second puzzle, it answers `day_25::NO_PART_2`
+    fn part2(&self, input:&Self::Input) -> Result<String>;
+
+    fn examples(&self) -> &'static [Example] {
+        &[]
+    }
+}
+
+struct Model<'a, P: Puzzle> {
+    puzzle:&'a P,
+    input:P::Input,
+}
+
+impl<'a, P: Puzzle> Parsed for Model<'a, P> {
+    fn solve(&self, part:Part) -> Result<String> {
+        match part {
+            Part::Part1 => self.puzzle.part1(&self.input),
+            Part::Part2 => self.puzzle.part2(&self.input),
+        }
+    }
+}
+
+impl<P: Puzzle> Solver for P {
+    fn day(&self) -> u8 {
+        Puzzle::day(self)
+    }
+
+    fn title(&self) -> &'static str {
+        Puzzle::title(self)
+    }
+
+    fn parse_input<'a>(&'a self, input:&str) -> Result<Box<dyn Parsed + 'a>> {
+        Ok(Box::new(Model{puzzle: self, input: self.parse(input)?}))
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        Puzzle::examples(self)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use util::SOLVERS;
+
+    #[test]
+    fn test_registry() {
+        let days = SOLVERS.iter().map(|solver| solver.day()).collect::<Vec<u8>>();
+        assert_eq!((1..=25).collect::<Vec<u8>>(), days);
+        assert!(SOLVERS.iter().all(|solver| !solver.title().is_empty()));
+    }
+
+    #[test]
+    fn test_examples() {
+        for solver in SOLVERS {
+            for example in solver.examples() {
+                assert_eq!(Ok(example.expected.to_string()), solver.solve(example.part, example.input),
+                           "day {} {:?}", solver.day(), example.part);
+            }
+        }
+    }
+}
@@ -0,0 +1,25 @@
+extern crate advent_of_code_2023;
+
+use advent_of_code_2023::{day_01, get_solution, Part, SolveError};
+
+const INPUT:&str = "1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet";
+
+#[test]
+fn test_get_solution() {
+    assert_eq!("142", get_solution(1, Part::Part1, INPUT.to_string()).unwrap());
+}
+
+#[test]
+fn test_day_solve() {
+    assert_eq!("142", day_01::solve(INPUT.to_string(), Part::Part1).unwrap());
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(Err(SolveError::UnknownDay(26)), get_solution(26, Part::Part1, INPUT.to_string()));
+    assert_eq!(Err(SolveError::UnknownPart(3)), Part::new(3));
+    assert_eq!(Err(SolveError::malformed(2, 1, "no digit in 'pqrstu'")), day_01::solve("1abc2\npqrstu".to_string(), Part::Part1));
+}